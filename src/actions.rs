@@ -0,0 +1,243 @@
+use std::time::Duration;
+
+use crossterm::event::KeyCode;
+
+use crate::Phase;
+
+/// 快捷键对应的操作
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Action {
+    Toggle,
+    Skip,
+    SwitchTo(Phase),
+    Reset,
+    GoTo,
+    BatchLabel,
+    EndBatch,
+    NewBlock,
+    Note,
+    LogSession,
+    Interruption,
+    Help,
+    EditConfig,
+    SwitchPreset,
+    SwitchPresetNow,
+    ToggleMute,
+    ToggleTime,
+    ResetCount,
+    NextProfile,
+}
+
+/// 帮助面板里的分组，按声明顺序展示
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Category {
+    Timer,
+    Session,
+    Settings,
+    Navigation,
+}
+
+impl Category {
+    pub const ALL: [Category; 4] = [
+        Category::Timer,
+        Category::Session,
+        Category::Settings,
+        Category::Navigation,
+    ];
+
+    pub fn title(self) -> &'static str {
+        match self {
+            Category::Timer => "Timer control",
+            Category::Session => "Session",
+            Category::Settings => "Settings",
+            Category::Navigation => "Navigation",
+        }
+    }
+}
+
+pub struct Binding {
+    pub keys: &'static [KeyCode],
+    pub action: Action,
+    pub category: Category,
+    pub description: &'static str,
+}
+
+/// 按键表：主循环按它分发按键，`?` 帮助面板也由它生成
+pub const BINDINGS: &[Binding] = &[
+    Binding {
+        keys: &[KeyCode::Char(' ')],
+        action: Action::Toggle,
+        category: Category::Timer,
+        description: "Start / pause",
+    },
+    Binding {
+        keys: &[KeyCode::Char('n'), KeyCode::Right],
+        action: Action::Skip,
+        category: Category::Timer,
+        description: "Skip to the next phase",
+    },
+    Binding {
+        keys: &[KeyCode::Char('1')],
+        action: Action::SwitchTo(Phase::Focus),
+        category: Category::Timer,
+        description: "Switch to focus",
+    },
+    Binding {
+        keys: &[KeyCode::Char('2')],
+        action: Action::SwitchTo(Phase::ShortBreak),
+        category: Category::Timer,
+        description: "Switch to a short break",
+    },
+    Binding {
+        keys: &[KeyCode::Char('3')],
+        action: Action::SwitchTo(Phase::LongBreak),
+        category: Category::Timer,
+        description: "Switch to a long break",
+    },
+    Binding {
+        keys: &[KeyCode::Char('r')],
+        action: Action::Reset,
+        category: Category::Timer,
+        description: "Reset the current phase",
+    },
+    Binding {
+        keys: &[KeyCode::Char('g')],
+        action: Action::GoTo,
+        category: Category::Timer,
+        description: "Set the remaining time (MM:SS)",
+    },
+    Binding {
+        keys: &[KeyCode::Char('b')],
+        action: Action::BatchLabel,
+        category: Category::Session,
+        description: "Start a labelled batch",
+    },
+    Binding {
+        keys: &[KeyCode::Char('B')],
+        action: Action::EndBatch,
+        category: Category::Session,
+        description: "End the current batch",
+    },
+    Binding {
+        keys: &[KeyCode::Char('w')],
+        action: Action::NewBlock,
+        category: Category::Session,
+        description: "Start a new work block (--block)",
+    },
+    Binding {
+        keys: &[KeyCode::Char('m')],
+        action: Action::Note,
+        category: Category::Session,
+        description: "Add a note to this session",
+    },
+    Binding {
+        keys: &[KeyCode::Char('c')],
+        action: Action::LogSession,
+        category: Category::Session,
+        description: "Log a focus session done elsewhere (type its length)",
+    },
+    Binding {
+        keys: &[KeyCode::Char('i')],
+        action: Action::Interruption,
+        category: Category::Session,
+        description: "Count an interruption in this focus session",
+    },
+    Binding {
+        keys: &[KeyCode::Char('p')],
+        action: Action::SwitchPreset,
+        category: Category::Settings,
+        description: "Switch duration preset (A / B) from the next phase",
+    },
+    Binding {
+        keys: &[KeyCode::Char('P')],
+        action: Action::SwitchPresetNow,
+        category: Category::Settings,
+        description: "Switch preset and resize the current phase too",
+    },
+    Binding {
+        keys: &[KeyCode::Char('o')],
+        action: Action::NextProfile,
+        category: Category::Settings,
+        description: "Switch to the next [profiles] entry and restart the phase",
+    },
+    Binding {
+        keys: &[KeyCode::Char('M')],
+        action: Action::ToggleMute,
+        category: Category::Settings,
+        description: "Mute / unmute the alert sound",
+    },
+    Binding {
+        keys: &[KeyCode::Char('v')],
+        action: Action::ToggleTime,
+        category: Category::Settings,
+        description: "Hide / show the remaining focus time",
+    },
+    Binding {
+        keys: &[KeyCode::Char('e')],
+        action: Action::EditConfig,
+        category: Category::Settings,
+        description: "Edit the config file in $EDITOR",
+    },
+    Binding {
+        keys: &[KeyCode::Char('?')],
+        action: Action::Help,
+        category: Category::Navigation,
+        description: "Show / hide this help",
+    },
+];
+
+/// 依次按下两个键触发的操作，用于不希望误触的操作
+pub struct Chord {
+    pub keys: [KeyCode; 2],
+    pub action: Action,
+    pub category: Category,
+    pub description: &'static str,
+}
+
+pub const CHORDS: &[Chord] = &[Chord {
+    keys: [KeyCode::Char('g'), KeyCode::Char('g')],
+    action: Action::ResetCount,
+    category: Category::Session,
+    description: "Reset the completed count to 0 (asks first)",
+}];
+
+/// 等待组合第二个键的时间；超时后第一个键按单键处理
+pub const CHORD_TIMEOUT: Duration = Duration::from_millis(600);
+
+pub fn lookup(code: KeyCode) -> Option<Action> {
+    BINDINGS
+        .iter()
+        .find(|binding| binding.keys.contains(&code))
+        .map(|binding| binding.action)
+}
+
+/// 这个键是否是某个组合的第一个键
+pub fn starts_chord(code: KeyCode) -> bool {
+    CHORDS.iter().any(|chord| chord.keys[0] == code)
+}
+
+pub fn lookup_chord(first: KeyCode, second: KeyCode) -> Option<Action> {
+    CHORDS
+        .iter()
+        .find(|chord| chord.keys == [first, second])
+        .map(|chord| chord.action)
+}
+
+/// 按键的显示名称；`ascii` 时方向键用英文名
+pub fn key_name(code: KeyCode, ascii: bool) -> String {
+    let arrow = |glyph: &str, name: &str| if ascii { name } else { glyph }.to_string();
+    match code {
+        KeyCode::Char(' ') => "Space".to_string(),
+        KeyCode::Char(c) => c.to_string(),
+        KeyCode::Right => arrow("→", "Right"),
+        KeyCode::Left => arrow("←", "Left"),
+        KeyCode::Up => arrow("↑", "Up"),
+        KeyCode::Down => arrow("↓", "Down"),
+        KeyCode::Esc => "Esc".to_string(),
+        KeyCode::Enter => "Enter".to_string(),
+        KeyCode::Tab => "Tab".to_string(),
+        KeyCode::Backspace => "Backspace".to_string(),
+        KeyCode::F(n) => format!("F{n}"),
+        other => format!("{other:?}"),
+    }
+}
@@ -0,0 +1,209 @@
+use std::collections::BTreeSet;
+use std::io::{self, Write};
+use std::path::PathBuf;
+use std::time::Duration;
+
+use crate::Phase;
+use crate::config::{AlertBackend, PhaseNames, Tone};
+use crate::notify;
+use crate::observer::PomodoroObserver;
+
+/// 到点时播放什么：声音文件优先，其次内置音色，都没有时响终端铃
+#[derive(Debug, Clone)]
+pub struct Cue {
+    pub sound: Option<PathBuf>,
+    pub tone: Option<Tone>,
+    pub fade_in: Duration,
+    /// 0.0..=1.0，只作用于文件和内置音色，终端铃的音量由终端决定
+    pub volume: f32,
+}
+
+impl Cue {
+    /// 是否会通过声卡播放（而不是响终端铃）
+    pub fn audible(&self) -> bool {
+        cfg!(feature = "sound") && (self.sound.is_some() || self.tone.is_some())
+    }
+}
+
+/// 阶段到点时依次触发启用的提醒方式；视觉铃要改界面，由 `PomodoroApp` 自己处理
+#[derive(Debug)]
+pub struct Alerts {
+    pub backends: BTreeSet<AlertBackend>,
+    pub cue: Cue,
+    pub names: PhaseNames,
+}
+
+impl PomodoroObserver for Alerts {
+    fn on_time_up(&mut self, phase: Phase) {
+        sound(&self.backends, &self.cue);
+        if self.backends.contains(&AlertBackend::Notify) {
+            let (title, body) = time_up_message(&self.names, phase);
+            // notify-send / osascript 要等进程结束，放到后台线程避免卡住计时
+            std::thread::spawn(move || notify::send(&title, body));
+        }
+    }
+}
+
+/// 阶段到点时桌面通知的标题和正文
+pub fn time_up_message(names: &PhaseNames, phase: Phase) -> (String, &'static str) {
+    let next = if phase == Phase::Focus {
+        "Time for a break."
+    } else {
+        "Time to focus."
+    };
+    (format!("{} finished", names.get(phase)), next)
+}
+
+/// 出声的部分：提示音能播放时播放，和响铃可以同时启用；只选了提示音但播放不了时退回响铃
+pub fn sound(backends: &BTreeSet<AlertBackend>, cue: &Cue) {
+    let wants_sound = backends.contains(&AlertBackend::Sound);
+    let audible = wants_sound && cue.audible();
+    if audible {
+        play(cue);
+    }
+    if backends.contains(&AlertBackend::Bell) || (wants_sound && !audible) {
+        ring();
+    }
+}
+
+/// 终端响铃（BEL）
+pub fn ring() {
+    let mut stdout = io::stdout();
+    let _ = stdout.write_all(b"\x07");
+    let _ = stdout.flush();
+}
+
+/// 视觉铃在终端里的样子：用 DECSCNM 整屏反色 `count` 次，反色与正常各持续 `step`
+pub fn flash(step: Duration, count: u32) {
+    let mut stdout = io::stdout();
+    for _ in 0..count {
+        let _ = stdout.write_all(b"\x1b[?5h");
+        let _ = stdout.flush();
+        std::thread::sleep(step);
+        let _ = stdout.write_all(b"\x1b[?5l");
+        let _ = stdout.flush();
+        std::thread::sleep(step);
+    }
+}
+
+/// 播放提示音：有文件或内置音色时在后台线程播放（文件的音量在 `fade_in` 内渐强），
+/// 否则响终端铃。无法播放时同样退回响铃。
+pub fn play(cue: &Cue) {
+    if !cue.audible() {
+        ring();
+        return;
+    }
+    let cue = cue.clone();
+    std::thread::spawn(move || {
+        let played = match (&cue.sound, cue.tone) {
+            (Some(path), _) => play_file(path, cue.fade_in, cue.volume),
+            (None, Some(tone)) => play_tone(tone, cue.volume),
+            (None, None) => Ok(()),
+        };
+        if played.is_err() {
+            ring();
+        }
+    });
+}
+
+#[cfg(feature = "sound")]
+fn open_player() -> anyhow::Result<(rodio::MixerDeviceSink, rodio::Player)> {
+    let mut sink = rodio::DeviceSinkBuilder::open_default_sink()?;
+    sink.log_on_drop(false);
+    let player = rodio::Player::connect_new(sink.mixer());
+    Ok((sink, player))
+}
+
+/// 同步播放文件直到结束
+#[cfg(feature = "sound")]
+pub fn play_file(path: &std::path::Path, fade_in: Duration, volume: f32) -> anyhow::Result<()> {
+    use rodio::Source;
+
+    let (_sink, player) = open_player()?;
+    player.set_volume(volume);
+    let source = rodio::Decoder::try_from(std::fs::File::open(path)?)?;
+    // 铃声本身无法渐强，只对文件生效
+    player.append(source.fade_in(fade_in));
+    player.sleep_until_end();
+    Ok(())
+}
+
+#[cfg(not(feature = "sound"))]
+pub fn play_file(_path: &std::path::Path, _fade_in: Duration, _volume: f32) -> anyhow::Result<()> {
+    anyhow::bail!("built without the `sound` feature")
+}
+
+/// 同步播放内置音色直到结束
+#[cfg(feature = "sound")]
+pub fn play_tone(tone: Tone, volume: f32) -> anyhow::Result<()> {
+    let (_sink, player) = open_player()?;
+    player.set_volume(volume);
+    player.append(tone_source(tone));
+    player.sleep_until_end();
+    Ok(())
+}
+
+#[cfg(not(feature = "sound"))]
+pub fn play_tone(_tone: Tone, _volume: f32) -> anyhow::Result<()> {
+    anyhow::bail!("built without the `sound` feature")
+}
+
+#[cfg(feature = "sound")]
+const SAMPLE_RATE: u32 = 44_100;
+
+/// 按音色合成一段单声道采样，整段不超过约 1.5 秒
+#[cfg(feature = "sound")]
+fn tone_source(tone: Tone) -> rodio::buffer::SamplesBuffer {
+    use std::f32::consts::TAU;
+    use std::num::NonZero;
+
+    // (频率, 开始秒, 时长秒, 衰减速度, 方波)
+    let notes: &[(f32, f32, f32, f32, bool)] = match tone {
+        // 上行两音
+        Tone::Chime => &[
+            (880.0, 0.0, 0.45, 6.0, false),
+            (1318.5, 0.18, 0.6, 5.0, false),
+        ],
+        // 三声短促的方波
+        Tone::Beep => &[
+            (1000.0, 0.0, 0.09, 0.0, true),
+            (1000.0, 0.16, 0.09, 0.0, true),
+            (1000.0, 0.32, 0.09, 0.0, true),
+        ],
+        // 基音加两个不协和泛音，长余音
+        Tone::Bell => &[
+            (523.3, 0.0, 1.5, 2.5, false),
+            (1444.0, 0.0, 0.9, 4.0, false),
+            (2616.0, 0.0, 0.5, 6.0, false),
+        ],
+        // 单个高音
+        Tone::Ding => &[(1568.0, 0.0, 0.7, 5.0, false)],
+    };
+    let length = notes
+        .iter()
+        .map(|&(_, start, secs, _, _)| start + secs)
+        .fold(0.0, f32::max);
+    let rate = SAMPLE_RATE as f32;
+    let mut samples = vec![0.0f32; (length * rate) as usize];
+    for &(freq, start, secs, decay, square) in notes {
+        let offset = (start * rate) as usize;
+        let count = (secs * rate) as usize;
+        for (i, sample) in samples.iter_mut().skip(offset).take(count).enumerate() {
+            let t = i as f32 / rate;
+            let wave = (TAU * freq * t).sin();
+            let wave = if square { wave.signum() * 0.3 } else { wave };
+            // 5ms 起音避免爆音，结尾的 10ms 淡出同理
+            let attack = (t / 0.005).min(1.0);
+            let release = ((secs - t) / 0.01).clamp(0.0, 1.0);
+            *sample += wave * (-decay * t).exp() * attack * release * 0.4;
+        }
+    }
+    for sample in &mut samples {
+        *sample = sample.clamp(-1.0, 1.0);
+    }
+    rodio::buffer::SamplesBuffer::new(
+        NonZero::new(1).expect("mono"),
+        NonZero::new(SAMPLE_RATE).expect("non-zero sample rate"),
+        samples,
+    )
+}
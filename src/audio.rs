@@ -0,0 +1,112 @@
+use std::sync::mpsc::Receiver;
+
+/// 默认音频输出设备变化（如拔下耳机）时发送一次 `()`。
+/// 只在 macOS 且启用 `audio-change` feature 时可用，其余情况返回 `None`。
+#[cfg(all(feature = "audio-change", target_os = "macos"))]
+pub fn watch() -> Option<Receiver<()>> {
+    use std::sync::mpsc::{self, Sender};
+
+    let (tx, rx) = mpsc::channel();
+    // 回调在 CoreAudio 自己的线程上执行，发送端随监听一直存活到进程退出
+    let sender: &'static Sender<()> = Box::leak(Box::new(tx));
+    coreaudio::listen(sender).then_some(rx)
+}
+
+#[cfg(not(all(feature = "audio-change", target_os = "macos")))]
+pub fn watch() -> Option<Receiver<()>> {
+    None
+}
+
+/// CoreAudio HAL 的最小绑定：只用到属性监听
+#[cfg(all(feature = "audio-change", target_os = "macos"))]
+mod coreaudio {
+    use std::ffi::c_void;
+    use std::ptr;
+    use std::sync::mpsc::Sender;
+
+    type AudioObjectId = u32;
+    type OsStatus = i32;
+
+    #[repr(C)]
+    struct PropertyAddress {
+        selector: u32,
+        scope: u32,
+        element: u32,
+    }
+
+    type Listener =
+        extern "C" fn(AudioObjectId, u32, *const PropertyAddress, *mut c_void) -> OsStatus;
+
+    const SYSTEM_OBJECT: AudioObjectId = 1;
+    const fn four_cc(code: &[u8; 4]) -> u32 {
+        u32::from_be_bytes(*code)
+    }
+    const DEFAULT_OUTPUT_DEVICE: u32 = four_cc(b"dOut");
+    const RUN_LOOP: u32 = four_cc(b"rnlp");
+    const SCOPE_GLOBAL: u32 = four_cc(b"glob");
+    const ELEMENT_MAIN: u32 = 0;
+
+    #[link(name = "CoreAudio", kind = "framework")]
+    unsafe extern "C" {
+        fn AudioObjectAddPropertyListener(
+            object: AudioObjectId,
+            address: *const PropertyAddress,
+            listener: Listener,
+            client_data: *mut c_void,
+        ) -> OsStatus;
+
+        fn AudioObjectSetPropertyData(
+            object: AudioObjectId,
+            address: *const PropertyAddress,
+            qualifier_size: u32,
+            qualifier: *const c_void,
+            data_size: u32,
+            data: *const c_void,
+        ) -> OsStatus;
+    }
+
+    extern "C" fn on_change(
+        _object: AudioObjectId,
+        _count: u32,
+        _addresses: *const PropertyAddress,
+        client_data: *mut c_void,
+    ) -> OsStatus {
+        // SAFETY: `client_data` 是 `listen` 传入的 `&'static Sender<()>`
+        let sender = unsafe { &*(client_data as *const Sender<()>) };
+        let _ = sender.send(());
+        0
+    }
+
+    /// 注册默认输出设备的监听；失败时返回 false
+    pub fn listen(sender: &'static Sender<()>) -> bool {
+        // 没有运行循环的命令行程序要让 HAL 在自己的线程上派发通知
+        let run_loop = PropertyAddress {
+            selector: RUN_LOOP,
+            scope: SCOPE_GLOBAL,
+            element: ELEMENT_MAIN,
+        };
+        let none: *const c_void = ptr::null();
+        let output = PropertyAddress {
+            selector: DEFAULT_OUTPUT_DEVICE,
+            scope: SCOPE_GLOBAL,
+            element: ELEMENT_MAIN,
+        };
+        // SAFETY: 地址和数据在调用期间有效；`sender` 是 'static，回调随时可以使用
+        unsafe {
+            AudioObjectSetPropertyData(
+                SYSTEM_OBJECT,
+                &run_loop,
+                0,
+                ptr::null(),
+                size_of::<*const c_void>() as u32,
+                (&raw const none).cast(),
+            );
+            AudioObjectAddPropertyListener(
+                SYSTEM_OBJECT,
+                &output,
+                on_change,
+                (sender as *const Sender<()>).cast_mut().cast(),
+            ) == 0
+        }
+    }
+}
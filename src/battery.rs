@@ -0,0 +1,41 @@
+use std::sync::mpsc::{self, Receiver};
+use std::thread;
+use std::time::Duration;
+
+use starship_battery::Manager;
+use starship_battery::units::ratio::percent;
+
+const POLL_INTERVAL: Duration = Duration::from_secs(30);
+
+/// 在后台线程定期读取电量（百分比，多块电池取平均）。
+/// 没有电池或无法读取时返回 `None`。
+pub fn watch() -> Option<Receiver<u8>> {
+    read_percent()?;
+    let (tx, rx) = mpsc::channel();
+    thread::spawn(move || {
+        loop {
+            if let Some(level) = read_percent()
+                && tx.send(level).is_err()
+            {
+                return;
+            }
+            thread::sleep(POLL_INTERVAL);
+        }
+    });
+    Some(rx)
+}
+
+fn read_percent() -> Option<u8> {
+    let manager = Manager::new().ok()?;
+    let levels: Vec<f32> = manager
+        .batteries()
+        .ok()?
+        .filter_map(Result::ok)
+        .map(|battery| battery.state_of_charge().get::<percent>())
+        .collect();
+    if levels.is_empty() {
+        return None;
+    }
+    let average = levels.iter().sum::<f32>() / levels.len() as f32;
+    Some(average.round().clamp(0.0, 100.0) as u8)
+}
@@ -0,0 +1,64 @@
+use std::cell::Cell;
+use std::fmt::Debug;
+use std::rc::Rc;
+use std::time::{Duration, Instant};
+
+use chrono::{Local, NaiveDateTime, TimeDelta};
+
+/// 计时器的时间来源：运行时使用系统时钟，测试/模拟时可手动推进
+pub trait Clock: Debug {
+    /// 单调时间，用于计算经过的时长
+    fn now(&self) -> Instant;
+
+    /// 本地墙上时间，用于历史记录与按天统计
+    fn local(&self) -> NaiveDateTime;
+}
+
+#[derive(Debug, Default, Clone, Copy)]
+pub struct SystemClock;
+
+impl Clock for SystemClock {
+    fn now(&self) -> Instant {
+        Instant::now()
+    }
+
+    fn local(&self) -> NaiveDateTime {
+        Local::now().naive_local()
+    }
+}
+
+/// A clock that only moves when [`ManualClock::advance`] is called.
+/// Clones share the same time, so a handle can be kept after giving one to the app.
+#[derive(Debug, Clone)]
+pub struct ManualClock {
+    start: Instant,
+    start_local: NaiveDateTime,
+    elapsed: Rc<Cell<Duration>>,
+}
+
+impl ManualClock {
+    pub fn new(start_local: NaiveDateTime) -> Self {
+        Self {
+            start: Instant::now(),
+            start_local,
+            elapsed: Rc::new(Cell::new(Duration::ZERO)),
+        }
+    }
+
+    pub fn advance(&self, by: Duration) {
+        self.elapsed.set(self.elapsed.get() + by);
+    }
+}
+
+impl Clock for ManualClock {
+    fn now(&self) -> Instant {
+        self.start + self.elapsed.get()
+    }
+
+    fn local(&self) -> NaiveDateTime {
+        let elapsed = TimeDelta::from_std(self.elapsed.get()).unwrap_or(TimeDelta::MAX);
+        self.start_local
+            .checked_add_signed(elapsed)
+            .unwrap_or(NaiveDateTime::MAX)
+    }
+}
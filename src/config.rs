@@ -0,0 +1,521 @@
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use anyhow::{Context, Result, anyhow};
+use serde::Deserialize;
+
+use crate::Phase;
+
+/// 配置文件内容，所有字段均可选；命令行显式传入的参数优先
+#[derive(Debug, Default, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct FileConfig {
+    pub focus_minutes: Option<u64>,
+    pub short_break_minutes: Option<u64>,
+    pub long_break_minutes: Option<u64>,
+    pub long_every: Option<u32>,
+    pub focus_first_break_long: Option<bool>,
+    pub start_phase: Option<StartPhase>,
+    /// 专注与短休息时长之比的合理范围，超出时启动时提醒
+    pub min_focus_ratio: Option<f64>,
+    pub max_focus_ratio: Option<f64>,
+    /// `p` 键切换的两组时长
+    pub preset: Option<Presets>,
+    /// `[profiles.<名称>]`：`o` 键按名称顺序轮换的多组时长
+    pub profiles: Option<BTreeMap<String, PresetMinutes>>,
+    pub mute: Option<bool>,
+    pub visual_bell: Option<bool>,
+    /// 到点时使用的提醒方式，如 `["bell", "notify"]`
+    pub alert: Option<Vec<AlertBackend>>,
+    /// 提示音文件路径
+    pub sound: Option<PathBuf>,
+    pub fade_in_ms: Option<u64>,
+    pub tone: Option<Tone>,
+    /// 提示音音量百分比
+    pub volume: Option<u8>,
+    pub tick_ms: Option<u64>,
+    /// 每秒最多重绘的次数
+    pub max_fps: Option<u16>,
+    pub ready_countdown_secs: Option<u64>,
+    pub warmup_secs: Option<u64>,
+    pub final_warning: Option<bool>,
+    pub show_clock: Option<bool>,
+    pub hide_time: Option<bool>,
+    pub round_log_minutes: Option<u64>,
+    /// 退出键列表，如 `["q", "ctrl+q"]`
+    pub quit_keys: Option<Vec<String>>,
+    pub media_control: Option<bool>,
+    pub media_follow_pause: Option<bool>,
+    pub dnd: Option<bool>,
+    pub dnd_on_cmd: Option<String>,
+    pub dnd_off_cmd: Option<String>,
+    pub live_notify: Option<bool>,
+    /// 为 false 时不捕获鼠标，保留终端自带的文本选择
+    pub mouse: Option<bool>,
+    /// 为 false 时在主缓冲区绘制，保留滚动历史
+    pub alternate_screen: Option<bool>,
+    pub auto_quit_idle_minutes: Option<u64>,
+    pub splash: Option<bool>,
+    pub done_screen: Option<bool>,
+    pub animations: Option<bool>,
+    pub ambient_bg: Option<bool>,
+    pub palette: Option<Palette>,
+    /// 主题文件路径，覆盖界面的全部颜色
+    pub theme_file: Option<PathBuf>,
+    pub bar_style: Option<BarStyle>,
+    pub ring: Option<bool>,
+    pub gauge_direction: Option<GaugeDirection>,
+    pub count_scope: Option<CountScope>,
+    pub resume_remaining: Option<bool>,
+    pub pause_after_long: Option<bool>,
+    pub overrun: Option<bool>,
+    pub alert_until_ack: Option<bool>,
+    pub duration_source: Option<PathBuf>,
+    /// 历史记录的 SQLite 副本（`sqlite` feature）
+    pub db: Option<PathBuf>,
+    pub min_break_secs: Option<u64>,
+    pub confirm_skip_ms: Option<u64>,
+    pub zero_behavior: Option<ZeroBehavior>,
+    /// 各阶段的边框样式与计时面板标题
+    pub phase_style: Option<PhaseStyles>,
+    pub focus_name: Option<String>,
+    /// 统计输出的数字格式，如 `de_DE`
+    pub locale: Option<String>,
+    pub short_name: Option<String>,
+    pub long_name: Option<String>,
+    /// 只用 ASCII 字符绘制图标
+    pub ascii: Option<bool>,
+    pub icons: Option<IconOverrides>,
+    pub daily_goal: Option<u32>,
+    pub track_debt: Option<bool>,
+    /// 欠账上限（默认为每日目标的两倍）
+    pub debt_cap: Option<u32>,
+    pub debt_skip_weekends: Option<bool>,
+    pub debt_window_days: Option<u32>,
+    /// 专注阶段页脚展示的激励语
+    pub messages: Option<Vec<String>>,
+    pub message_order: Option<MessageOrder>,
+}
+
+/// Built-in alert sounds, synthesized so no audio file is needed.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize, clap::ValueEnum)]
+#[serde(rename_all = "lowercase")]
+pub enum Tone {
+    /// 上行的两个音
+    Chime,
+    /// 三声短促的电子音
+    Beep,
+    /// 带泛音的钟声，余音较长
+    Bell,
+    /// 单个清脆的高音
+    Ding,
+}
+
+impl Tone {
+    pub fn name(self) -> &'static str {
+        match self {
+            Tone::Chime => "chime",
+            Tone::Beep => "beep",
+            Tone::Bell => "bell",
+            Tone::Ding => "ding",
+        }
+    }
+}
+
+/// One way of signalling that a phase has ended (`--alert bell,notify`).
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Deserialize, clap::ValueEnum)]
+#[serde(rename_all = "lowercase")]
+pub enum AlertBackend {
+    /// 终端响铃（BEL）
+    Bell,
+    /// `--sound` 的文件或 `--tone` 的内置音色，无法播放时退回响铃
+    Sound,
+    /// 桌面通知
+    Notify,
+    /// 整屏反色闪烁（视觉铃）
+    Flash,
+}
+
+impl AlertBackend {
+    pub fn name(self) -> &'static str {
+        match self {
+            AlertBackend::Bell => "bell",
+            AlertBackend::Sound => "sound",
+            AlertBackend::Notify => "notify",
+            AlertBackend::Flash => "flash",
+        }
+    }
+}
+
+/// How a motivational message is picked for each focus session.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum MessageOrder {
+    #[default]
+    Sequential,
+    Random,
+}
+
+/// How the progress panel is drawn.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Deserialize, clap::ValueEnum)]
+#[serde(rename_all = "lowercase")]
+pub enum BarStyle {
+    /// 填充式进度条
+    #[default]
+    Gauge,
+    /// 左侧已用、右侧剩余，中间有分隔线
+    Split,
+}
+
+/// Whether the gauge grows or empties as time passes.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Deserialize, clap::ValueEnum)]
+#[serde(rename_all = "lowercase")]
+pub enum GaugeDirection {
+    /// 随已用时间增长
+    #[default]
+    Fill,
+    /// 随剩余时间减少
+    Drain,
+}
+
+/// What the header's "Completed" number counts.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Deserialize, clap::ValueEnum)]
+#[serde(rename_all = "lowercase")]
+pub enum CountScope {
+    /// 本次运行（含恢复的数量），同时决定长休息的节奏
+    #[default]
+    Run,
+    /// 今天完成的专注
+    Day,
+    /// 本周（周一起）完成的专注，启动时从历史记录统计
+    Week,
+}
+
+/// Phase colors, and whether phases also differ by border and icon.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Deserialize, clap::ValueEnum)]
+#[serde(rename_all = "lowercase")]
+pub enum Palette {
+    /// 绿 / 青 / 品红
+    #[default]
+    Default,
+    /// 红绿色弱也分得清的橙 / 天蓝 / 深蓝，并给每个阶段不同的边框和图标
+    Colorblind,
+}
+
+impl Palette {
+    /// 未在 `[phase_style]` 里指定边框时使用的边框
+    pub fn border(self, phase: Phase) -> Option<Border> {
+        match self {
+            Palette::Default => None,
+            Palette::Colorblind => Some(match phase {
+                Phase::Focus => Border::Thick,
+                Phase::ShortBreak => Border::Rounded,
+                Phase::LongBreak => Border::Double,
+            }),
+        }
+    }
+}
+
+/// Which phase a new timer begins with.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize, clap::ValueEnum)]
+#[serde(rename_all = "lowercase")]
+pub enum StartPhase {
+    Focus,
+    Short,
+    Long,
+}
+
+impl StartPhase {
+    pub fn phase(self) -> Phase {
+        match self {
+            StartPhase::Focus => Phase::Focus,
+            StartPhase::Short => Phase::ShortBreak,
+            StartPhase::Long => Phase::LongBreak,
+        }
+    }
+}
+
+/// What happens when the remaining time reaches zero while paused (e.g. "go to 00:00").
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Deserialize, clap::ValueEnum)]
+#[serde(rename_all = "lowercase")]
+pub enum ZeroBehavior {
+    /// 立即结束当前阶段，下一阶段保持暂停
+    Finish,
+    /// 停在 00:00，恢复计时后再结束
+    #[default]
+    Hold,
+}
+
+/// `[phase_style.focus]` 等小节
+#[derive(Debug, Default, Clone, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct PhaseStyles {
+    pub focus: Option<PhaseStyle>,
+    pub short_break: Option<PhaseStyle>,
+    pub long_break: Option<PhaseStyle>,
+}
+
+impl PhaseStyles {
+    pub fn get(&self, phase: Phase) -> Option<&PhaseStyle> {
+        match phase {
+            Phase::Focus => self.focus.as_ref(),
+            Phase::ShortBreak => self.short_break.as_ref(),
+            Phase::LongBreak => self.long_break.as_ref(),
+        }
+    }
+}
+
+#[derive(Debug, Default, Clone, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct PhaseStyle {
+    pub border: Option<Border>,
+    /// 计时面板的标题（默认 "Timer"）
+    pub title: Option<String>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Border {
+    Rounded,
+    Plain,
+    Thick,
+    Double,
+}
+
+/// `[preset.a]` / `[preset.b]` 小节；只写一组时另一组就是平时的时长
+#[derive(Debug, Default, Clone, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct Presets {
+    pub a: Option<PresetMinutes>,
+    pub b: Option<PresetMinutes>,
+}
+
+/// 未写出的时长沿用平时的设置
+#[derive(Debug, Default, Clone, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct PresetMinutes {
+    pub focus_minutes: Option<u64>,
+    pub short_break_minutes: Option<u64>,
+    pub long_break_minutes: Option<u64>,
+}
+
+/// `[icons]` 小节，覆盖预设中的单个图标
+#[derive(Debug, Default, Clone, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct IconOverrides {
+    pub focus: Option<String>,
+    pub short_break: Option<String>,
+    pub long_break: Option<String>,
+    pub running: Option<String>,
+    pub paused: Option<String>,
+}
+
+/// 界面使用的图标
+#[derive(Debug, Clone)]
+pub struct Icons {
+    pub focus: String,
+    pub short_break: String,
+    pub long_break: String,
+    pub running: String,
+    pub paused: String,
+    /// 其余装饰字符（快捷键图标、输入光标等）也换成 ASCII
+    pub ascii: bool,
+}
+
+impl Icons {
+    pub fn preset(ascii: bool) -> Self {
+        let (focus, brk, running, paused) = if ascii {
+            ("*", "~", ">", "||")
+        } else {
+            ("●", "☕", "⏱", "⏸")
+        };
+        Self {
+            focus: focus.to_string(),
+            short_break: brk.to_string(),
+            long_break: brk.to_string(),
+            running: running.to_string(),
+            paused: paused.to_string(),
+            ascii,
+        }
+    }
+
+    /// 让长休息的图标与短休息不同，不靠颜色也能认出阶段
+    pub fn distinct_breaks(mut self) -> Self {
+        self.long_break = if self.ascii { "~~" } else { "☾" }.to_string();
+        self
+    }
+
+    pub fn with_overrides(mut self, overrides: &IconOverrides) -> Self {
+        let fields = [
+            (&mut self.focus, &overrides.focus),
+            (&mut self.short_break, &overrides.short_break),
+            (&mut self.long_break, &overrides.long_break),
+            (&mut self.running, &overrides.running),
+            (&mut self.paused, &overrides.paused),
+        ];
+        for (icon, custom) in fields {
+            if let Some(custom) = custom {
+                icon.clone_from(custom);
+            }
+        }
+        self
+    }
+
+    pub fn phase(&self, phase: Phase) -> &str {
+        match phase {
+            Phase::Focus => &self.focus,
+            Phase::ShortBreak => &self.short_break,
+            Phase::LongBreak => &self.long_break,
+        }
+    }
+}
+
+/// 阶段的显示名（`--focus-name` 等）；历史文件里仍用固定的阶段标识
+#[derive(Debug, Clone)]
+pub struct PhaseNames {
+    pub focus: String,
+    pub short_break: String,
+    pub long_break: String,
+}
+
+impl PhaseNames {
+    pub fn new(focus: Option<&str>, short_break: Option<&str>, long_break: Option<&str>) -> Self {
+        let name = |custom: Option<&str>, phase: Phase| custom.unwrap_or(phase.name()).to_string();
+        Self {
+            focus: name(focus, Phase::Focus),
+            short_break: name(short_break, Phase::ShortBreak),
+            long_break: name(long_break, Phase::LongBreak),
+        }
+    }
+
+    pub fn get(&self, phase: Phase) -> &str {
+        match phase {
+            Phase::Focus => &self.focus,
+            Phase::ShortBreak => &self.short_break,
+            Phase::LongBreak => &self.long_break,
+        }
+    }
+}
+
+impl Default for PhaseNames {
+    fn default() -> Self {
+        Self::new(None, None, None)
+    }
+}
+
+/// Default location: `<config dir>/pomodoros/config.toml`.
+pub fn default_path() -> Option<PathBuf> {
+    dirs::config_dir().map(|dir| dir.join("pomodoros").join("config.toml"))
+}
+
+/// Load the config file. An explicit `path` must exist; the default one is optional.
+pub fn load(path: Option<&Path>) -> Result<FileConfig> {
+    let (path, required) = match path {
+        Some(path) => (path.to_path_buf(), true),
+        None => match default_path() {
+            Some(path) => (path, false),
+            None => return Ok(FileConfig::default()),
+        },
+    };
+    if !required && !path.exists() {
+        return Ok(FileConfig::default());
+    }
+    let text = fs::read_to_string(&path)
+        .with_context(|| format!("failed to read config file {}", path.display()))?;
+    parse(&text).with_context(|| format!("invalid config file {}", path.display()))
+}
+
+/// 解析配置文件内容。语法错误、类型不符、超出范围的数字和未知键都只返回错误，不会 panic；
+/// 时长不能为零、不能超过一天等检查留给 `build_config`。
+pub fn parse(text: &str) -> Result<FileConfig> {
+    toml::from_str(text).map_err(|err| {
+        let message = err.message().to_string();
+        match suggest_field(&message) {
+            Some((field, suggestion)) => {
+                anyhow!("{err}\nunknown key `{field}`, did you mean `{suggestion}`?")
+            }
+            None => anyhow!("{err}"),
+        }
+    })
+}
+
+/// 从 serde 的 "unknown field `x`, expected one of `a`, `b`" 信息中找出最接近的键名
+fn suggest_field(message: &str) -> Option<(String, String)> {
+    let rest = message.strip_prefix("unknown field ")?;
+    let mut names = rest.split('`').skip(1).step_by(2);
+    let field = names.next()?;
+    names
+        .map(|candidate| (edit_distance(field, candidate), candidate))
+        .filter(|(distance, _)| *distance <= 3)
+        .min_by_key(|(distance, _)| *distance)
+        .map(|(_, candidate)| (field.to_string(), candidate.to_string()))
+}
+
+fn edit_distance(a: &str, b: &str) -> usize {
+    let b: Vec<char> = b.chars().collect();
+    let mut prev: Vec<usize> = (0..=b.len()).collect();
+    for (i, ca) in a.chars().enumerate() {
+        let mut cur = vec![i + 1; b.len() + 1];
+        for (j, cb) in b.iter().enumerate() {
+            let cost = usize::from(ca != *cb);
+            cur[j + 1] = (prev[j] + cost).min(prev[j + 1] + 1).min(cur[j] + 1);
+        }
+        prev = cur;
+    }
+    prev[b.len()]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_rejects_malformed_toml() {
+        for text in [
+            "focus_minutes =",
+            "focus_minutes = 25 25",
+            "[preset",
+            "[preset.a]\nfocus_minutes = \"ten\"",
+            "focus_minutes = -5",
+            "focus_minutes = 99999999999999999999",
+            "volume = 300",
+            "long_every = 1.5",
+            "mute = \"yes\"",
+            "focus_minutes = 25\nfocus_minutes = 30",
+            "\u{0}",
+        ] {
+            assert!(parse(text).is_err(), "`{text}` should be rejected");
+        }
+    }
+
+    #[test]
+    fn misspelled_key_suggests_the_known_one() {
+        let err = parse("foucs_minutes = 25").unwrap_err().to_string();
+        assert!(err.contains("foucs_minutes"), "{err}");
+        assert!(err.contains("did you mean `focus_minutes`?"), "{err}");
+        // 差得太远的键只报未知，不乱猜
+        let err = parse("xyzzy = 1").unwrap_err().to_string();
+        assert!(
+            err.contains("xyzzy") && !err.contains("did you mean"),
+            "{err}"
+        );
+    }
+
+    #[test]
+    fn edit_distance_counts_single_edits() {
+        assert_eq!(edit_distance("focus", "focus"), 0);
+        assert_eq!(edit_distance("foucs", "focus"), 2);
+        assert_eq!(edit_distance("mute", "mutes"), 1);
+        assert_eq!(edit_distance("", "tone"), 4);
+    }
+
+    #[test]
+    fn parse_accepts_empty_and_known_keys() {
+        assert!(parse("").is_ok());
+        let config = parse("focus_minutes = 50\n[preset.a]\nshort_break_minutes = 10").unwrap();
+        assert_eq!(config.focus_minutes, Some(50));
+        let preset = config.preset.and_then(|presets| presets.a).unwrap();
+        assert_eq!(preset.short_break_minutes, Some(10));
+    }
+}
@@ -0,0 +1,80 @@
+use std::path::Path;
+
+use anyhow::Result;
+
+use crate::history::SessionRecord;
+
+/// 历史记录的 SQLite 副本（`--db`）：CSV 照常写入，每条记录再插入 `sessions` 表，方便用 SQL 查询
+#[cfg(feature = "sqlite")]
+pub struct Database {
+    conn: rusqlite::Connection,
+}
+
+#[cfg(feature = "sqlite")]
+const SCHEMA: &str = "
+CREATE TABLE IF NOT EXISTS sessions (
+    id            INTEGER PRIMARY KEY,
+    start         TEXT NOT NULL,
+    end           TEXT NOT NULL,
+    phase         TEXT NOT NULL,
+    duration_secs INTEGER NOT NULL,
+    batch         TEXT,
+    interruptions INTEGER NOT NULL DEFAULT 0,
+    note          TEXT,
+    completed     INTEGER NOT NULL DEFAULT 1
+);
+CREATE INDEX IF NOT EXISTS sessions_start ON sessions (start);
+";
+
+#[cfg(feature = "sqlite")]
+impl Database {
+    /// 打开数据库，第一次使用时建表
+    pub fn open(path: &Path) -> Result<Self> {
+        use anyhow::Context;
+
+        if let Some(parent) = path.parent() {
+            std::fs::create_dir_all(parent)
+                .with_context(|| format!("failed to create {}", parent.display()))?;
+        }
+        let conn = rusqlite::Connection::open(path)
+            .with_context(|| format!("failed to open database {}", path.display()))?;
+        conn.execute_batch(SCHEMA)
+            .with_context(|| format!("failed to create the schema in {}", path.display()))?;
+        Ok(Self { conn })
+    }
+
+    pub fn insert(&self, record: &SessionRecord) -> Result<()> {
+        // 与 CSV 相同的 RFC 3339 格式，SQLite 的日期函数会按偏移换算成 UTC
+        let time = crate::history::format_time;
+        self.conn.execute(
+            "INSERT INTO sessions
+                 (start, end, phase, duration_secs, batch, interruptions, note, completed)
+             VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8)",
+            rusqlite::params![
+                time(record.start),
+                time(record.end),
+                record.phase.key(),
+                i64::try_from(record.duration_secs).unwrap_or(i64::MAX),
+                record.batch,
+                record.interruptions,
+                record.note,
+                record.completed,
+            ],
+        )?;
+        Ok(())
+    }
+}
+
+#[cfg(not(feature = "sqlite"))]
+pub struct Database;
+
+#[cfg(not(feature = "sqlite"))]
+impl Database {
+    pub fn open(_path: &Path) -> Result<Self> {
+        anyhow::bail!("built without the `sqlite` feature")
+    }
+
+    pub fn insert(&self, _record: &SessionRecord) -> Result<()> {
+        Ok(())
+    }
+}
@@ -0,0 +1,96 @@
+use std::process::{Command, Stdio};
+use std::sync::mpsc::{self, Sender};
+use std::thread::{self, JoinHandle};
+
+/// 专注开始后开启系统勿扰，休息和退出时关闭；只关闭由我们开启的勿扰
+#[derive(Debug)]
+pub struct DoNotDisturb {
+    on_cmd: String,
+    off_cmd: String,
+    active: bool,
+    worker: Option<(Sender<String>, JoinHandle<()>)>,
+}
+
+impl DoNotDisturb {
+    /// 命令在一个后台线程上依次执行，避免阻塞界面，也保证开关的先后顺序不乱
+    pub fn new(on_cmd: String, off_cmd: String) -> Self {
+        let (tx, rx) = mpsc::channel::<String>();
+        let handle = thread::spawn(move || {
+            for command in rx {
+                run(&command);
+            }
+        });
+        Self {
+            on_cmd,
+            off_cmd,
+            active: false,
+            worker: Some((tx, handle)),
+        }
+    }
+
+    /// 每次循环调用；只在状态变化时执行命令，所以手动跳过阶段也能正确切换
+    pub fn sync(&mut self, focusing: bool) {
+        if focusing == self.active {
+            return;
+        }
+        self.active = focusing;
+        let command = if focusing {
+            self.on_cmd.clone()
+        } else {
+            self.off_cmd.clone()
+        };
+        self.send(command);
+    }
+
+    /// 退出时关闭勿扰，等排在前面的命令和它都执行完再返回，确保进程退出前完成
+    pub fn restore(&mut self) {
+        self.sync(false);
+        if let Some((tx, handle)) = self.worker.take() {
+            drop(tx);
+            let _ = handle.join();
+        }
+    }
+
+    fn send(&self, command: String) {
+        if let Some((tx, _)) = &self.worker {
+            let _ = tx.send(command);
+        }
+    }
+}
+
+/// 各平台默认的开启/关闭命令
+pub fn default_commands() -> Option<(&'static str, &'static str)> {
+    if cfg!(target_os = "linux") {
+        // GNOME：隐藏通知横幅
+        Some((
+            "gsettings set org.gnome.desktop.notifications show-banners false",
+            "gsettings set org.gnome.desktop.notifications show-banners true",
+        ))
+    } else if cfg!(target_os = "macos") {
+        // 需要在“快捷指令”里建好这两个快捷指令
+        Some((
+            "shortcuts run 'pomodoros dnd on'",
+            "shortcuts run 'pomodoros dnd off'",
+        ))
+    } else {
+        None
+    }
+}
+
+fn run(command: &str) {
+    let mut shell = if cfg!(windows) {
+        let mut shell = Command::new("cmd");
+        shell.arg("/C");
+        shell
+    } else {
+        let mut shell = Command::new("sh");
+        shell.arg("-c");
+        shell
+    };
+    let _ = shell
+        .arg(command)
+        .stdin(Stdio::null())
+        .stdout(Stdio::null())
+        .stderr(Stdio::null())
+        .status();
+}
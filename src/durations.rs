@@ -0,0 +1,60 @@
+use std::fs::File;
+use std::io::{BufRead, BufReader};
+use std::path::Path;
+use std::sync::mpsc::{self, Receiver};
+use std::thread;
+use std::time::Duration;
+
+use crate::parse_duration;
+
+/// 来源中的一行：`Ok(None)` 是空行（用默认时长），`Err` 是无法解析的行
+pub type Line = Result<Option<Duration>, String>;
+
+/// 在后台线程逐行读取 `--duration-source`：普通文件读完即止；
+/// FIFO 在写入方关闭后重新打开，等待下一个写入方。
+/// 打开 FIFO 会阻塞到有写入方为止，所以不能放在界面线程里。
+pub fn watch(path: &Path) -> Receiver<Line> {
+    let path = path.to_path_buf();
+    let (tx, rx) = mpsc::channel();
+    thread::spawn(move || {
+        while let Ok(file) = File::open(&path) {
+            for line in BufReader::new(file).lines() {
+                let Ok(line) = line else {
+                    break;
+                };
+                if tx.send(parse(&line)).is_err() {
+                    return;
+                }
+            }
+            if !is_fifo(&path) {
+                return;
+            }
+        }
+    });
+    rx
+}
+
+fn parse(line: &str) -> Line {
+    let line = line.trim();
+    if line.is_empty() {
+        return Ok(None);
+    }
+    match parse_duration(line)? {
+        duration if duration.is_zero() => {
+            Err(format!("expected a non-zero duration, got `{line}`"))
+        }
+        duration => Ok(Some(duration)),
+    }
+}
+
+#[cfg(unix)]
+fn is_fifo(path: &Path) -> bool {
+    use std::os::unix::fs::FileTypeExt;
+
+    std::fs::metadata(path).is_ok_and(|meta| meta.file_type().is_fifo())
+}
+
+#[cfg(not(unix))]
+fn is_fifo(_path: &Path) -> bool {
+    false
+}
@@ -0,0 +1,119 @@
+use std::fmt::Write as _;
+use std::fs;
+use std::path::Path;
+
+use anyhow::{Context, Result, ensure};
+use chrono::NaiveDate;
+
+use crate::Phase;
+use crate::history::{self, SessionRecord, csv_field};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, clap::ValueEnum)]
+pub enum ExportFormat {
+    /// Toggl Track CSV import
+    Toggl,
+}
+
+/// Toggl 导入必填 Email、Start date、Start time、Duration；其余列留空即可
+const TOGGL_HEADER: &str =
+    "User,Email,Client,Project,Task,Description,Billable,Start date,Start time,Duration,Tags";
+
+pub fn run(
+    path: &Path,
+    format: ExportFormat,
+    from: Option<NaiveDate>,
+    to: Option<NaiveDate>,
+    email: &str,
+    out: Option<&Path>,
+) -> Result<()> {
+    if let (Some(from), Some(to)) = (from, to) {
+        ensure!(from <= to, "--from {from} is after --to {to}");
+    }
+    let records = history::load(path)?;
+    let records: Vec<&SessionRecord> = records
+        .iter()
+        .filter(|r| from.is_none_or(|from| r.start.date() >= from))
+        .filter(|r| to.is_none_or(|to| r.start.date() <= to))
+        .collect();
+    let text = match format {
+        ExportFormat::Toggl => toggl(&records, email),
+    };
+    match out {
+        Some(out) => {
+            fs::write(out, text).with_context(|| format!("failed to write {}", out.display()))
+        }
+        None => {
+            print!("{text}");
+            Ok(())
+        }
+    }
+}
+
+/// 专注记为 billable，休息记为非 billable；描述取批次标签，没有则用阶段名
+pub fn toggl(records: &[&SessionRecord], email: &str) -> String {
+    let mut out = String::new();
+    let _ = writeln!(out, "{TOGGL_HEADER}");
+    for record in records {
+        let secs = record.duration_secs;
+        let description = record.batch.as_deref().unwrap_or(record.phase.name());
+        let _ = writeln!(
+            out,
+            ",{},,,,{},{},{},{},{:02}:{:02}:{:02},pomodoros",
+            csv_field(email),
+            csv_field(description),
+            if record.phase == Phase::Focus {
+                "Yes"
+            } else {
+                "No"
+            },
+            record.start.format("%Y-%m-%d"),
+            record.start.format("%H:%M:%S"),
+            secs / 3600,
+            secs / 60 % 60,
+            secs % 60
+        );
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::NaiveDateTime;
+
+    use super::*;
+
+    fn record(start: &str, phase: Phase, duration_secs: u64, batch: Option<&str>) -> SessionRecord {
+        let start = NaiveDateTime::parse_from_str(start, "%Y-%m-%d %H:%M:%S").unwrap();
+        SessionRecord {
+            start,
+            end: start + chrono::TimeDelta::seconds(duration_secs as i64),
+            phase,
+            duration_secs,
+            batch: batch.map(str::to_string),
+            interruptions: 0,
+            note: None,
+            completed: true,
+        }
+    }
+
+    #[test]
+    fn toggl_rows_follow_the_import_columns() {
+        let focus = record(
+            "2024-03-04 09:00:05",
+            Phase::Focus,
+            3723,
+            Some("Report, draft"),
+        );
+        let rest = record("2024-03-04 10:02:08", Phase::ShortBreak, 300, None);
+        let csv = toggl(&[&focus, &rest], "me@example.com");
+        let lines: Vec<&str> = csv.lines().collect();
+        assert_eq!(
+            lines,
+            [
+                "User,Email,Client,Project,Task,Description,Billable,Start date,Start time,Duration,Tags",
+                ",me@example.com,,,,\"Report, draft\",Yes,2024-03-04,09:00:05,01:02:03,pomodoros",
+                ",me@example.com,,,,Short Break,No,2024-03-04,10:02:08,00:05:00,pomodoros",
+            ]
+        );
+    }
+}
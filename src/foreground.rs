@@ -0,0 +1,78 @@
+use std::sync::mpsc::{self, Receiver};
+use std::thread;
+use std::time::Duration;
+
+const POLL_INTERVAL: Duration = Duration::from_secs(1);
+
+/// 在后台线程轮询前台进程，名称匹配与否变化时发送 `true`/`false`。
+/// 当前平台无法检测时返回 `None`。
+pub fn watch(name: &str) -> Option<Receiver<bool>> {
+    foreground_process()?;
+    let name = name.to_lowercase();
+    let (tx, rx) = mpsc::channel();
+    thread::spawn(move || {
+        let mut last = None;
+        loop {
+            // 检测失败时视为仍在前台，避免误暂停
+            let focused = foreground_process().is_none_or(|names| names.contains(&name));
+            if last != Some(focused) {
+                if tx.send(focused).is_err() {
+                    return;
+                }
+                last = Some(focused);
+            }
+            thread::sleep(POLL_INTERVAL);
+        }
+    });
+    Some(rx)
+}
+
+/// 前台窗口所属进程的候选名称（小写）
+#[cfg(target_os = "linux")]
+fn foreground_process() -> Option<Vec<String>> {
+    use std::process::Command;
+
+    let output = Command::new("xdotool")
+        .args(["getactivewindow", "getwindowpid"])
+        .output()
+        .ok()?;
+    if !output.status.success() {
+        return None;
+    }
+    let pid = String::from_utf8_lossy(&output.stdout).trim().to_string();
+    let mut names = Vec::new();
+    if let Ok(comm) = std::fs::read_to_string(format!("/proc/{pid}/comm")) {
+        names.push(comm.trim().to_lowercase());
+    }
+    if let Ok(exe) = std::fs::read_link(format!("/proc/{pid}/exe"))
+        && let Some(file) = exe.file_name()
+    {
+        names.push(file.to_string_lossy().to_lowercase());
+    }
+    Some(names)
+}
+
+#[cfg(target_os = "macos")]
+fn foreground_process() -> Option<Vec<String>> {
+    use std::process::Command;
+
+    let output = Command::new("osascript")
+        .args([
+            "-e",
+            "tell application \"System Events\" to get name of first application process whose frontmost is true",
+        ])
+        .output()
+        .ok()?;
+    if !output.status.success() {
+        return None;
+    }
+    let name = String::from_utf8_lossy(&output.stdout)
+        .trim()
+        .to_lowercase();
+    Some(vec![name])
+}
+
+#[cfg(not(any(target_os = "linux", target_os = "macos")))]
+fn foreground_process() -> Option<Vec<String>> {
+    None
+}
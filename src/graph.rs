@@ -0,0 +1,81 @@
+use std::collections::BTreeMap;
+use std::fmt::Write as _;
+use std::path::Path;
+
+use anyhow::Result;
+use chrono::{NaiveDate, TimeDelta};
+
+use crate::history;
+use crate::stats::{self, DayTotals};
+use crate::timeline::format_secs;
+
+const BAR_WIDTH: u64 = 40;
+/// 1/8 格的方块，用于让条形长度更精确
+const EIGHTHS: [char; 8] = [' ', '▏', '▎', '▍', '▌', '▋', '▊', '▉'];
+
+pub fn run(path: &Path, today: NaiveDate, days: u32, ascii: bool) -> Result<()> {
+    let records = history::load(path)?;
+    print!(
+        "{}",
+        render(&stats::focus_by_day(&records), today, days, ascii)
+    );
+    Ok(())
+}
+
+/// 最近 `days` 天（含今天）每天的专注时长条形图，按其中最长的一天缩放
+pub fn render(
+    totals: &BTreeMap<NaiveDate, DayTotals>,
+    today: NaiveDate,
+    days: u32,
+    ascii: bool,
+) -> String {
+    let dates: Vec<NaiveDate> = (0..days)
+        .rev()
+        .filter_map(|back| today.checked_sub_signed(TimeDelta::days(i64::from(back))))
+        .collect();
+    let focus = |date: &NaiveDate| totals.get(date).map_or(0, |d| d.focus_secs);
+    let max = dates.iter().map(focus).max().unwrap_or(0);
+
+    let mut out = String::new();
+    let _ = writeln!(out, "Focus per day, last {days} days");
+    for date in &dates {
+        let secs = focus(date);
+        let bar = bar(secs, max, ascii);
+        let value = if secs == 0 {
+            String::new()
+        } else {
+            format_secs(secs)
+        };
+        let line = format!("{} {}  {bar}  {value}", date, date.format("%a"));
+        let _ = writeln!(out, "{}", line.trim_end());
+    }
+    if max == 0 {
+        let _ = writeln!(out, "No focus recorded in this range.");
+    }
+    out
+}
+
+/// 固定宽度的条形，空白补齐以便后面的数值对齐
+fn bar(secs: u64, max: u64, ascii: bool) -> String {
+    if max == 0 {
+        return " ".repeat(BAR_WIDTH as usize);
+    }
+    let mut bar = String::new();
+    if ascii {
+        let cells = secs * BAR_WIDTH / max;
+        bar.push_str(&"#".repeat(cells as usize));
+    } else {
+        let eighths = secs * BAR_WIDTH * 8 / max;
+        bar.push_str(&"█".repeat((eighths / 8) as usize));
+        if !eighths.is_multiple_of(8) {
+            bar.push(EIGHTHS[(eighths % 8) as usize]);
+        }
+    }
+    // 有专注但不足一格时至少画一点
+    if secs > 0 && bar.is_empty() {
+        bar.push(if ascii { '.' } else { '▏' });
+    }
+    let used = bar.chars().count();
+    bar.push_str(&" ".repeat(BAR_WIDTH as usize - used));
+    bar
+}
@@ -0,0 +1,459 @@
+use std::collections::BTreeMap;
+use std::fs::{self, OpenOptions};
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+use anyhow::{Context, Result, anyhow, bail};
+use chrono::{
+    DateTime, FixedOffset, Local, LocalResult, NaiveDate, NaiveDateTime, Offset, SecondsFormat,
+    TimeDelta, TimeZone,
+};
+
+use crate::Phase;
+
+const HEADER: &str = "start,end,phase,duration_secs,batch,interruptions,note,completed";
+const BATCH_HEADER: &str = "start,end,label,sessions,focus_secs";
+const GOAL_HEADER: &str = "day,goal";
+/// 旧版本写入的不带时区的本地时间，读取时按当前时区理解
+const LEGACY_TIME_FORMAT: &str = "%Y-%m-%d %H:%M:%S";
+
+/// 一条已结束阶段的记录（本地时间；文件里存的是带时区偏移的 RFC 3339）
+#[derive(Debug, Clone)]
+pub struct SessionRecord {
+    pub start: NaiveDateTime,
+    pub end: NaiveDateTime,
+    pub phase: Phase,
+    /// 实际计时的秒数（不含暂停）
+    pub duration_secs: u64,
+    /// 所属批次的标签
+    pub batch: Option<String>,
+    /// 阶段中途被暂停的次数
+    pub interruptions: u32,
+    pub note: Option<String>,
+    /// 为 false 时是退出时记下的未完成阶段，`duration_secs` 为实际计时的部分
+    pub completed: bool,
+}
+
+/// 一个已结束批次的汇总
+#[derive(Debug, Clone)]
+pub struct BatchRecord {
+    pub start: NaiveDateTime,
+    pub end: NaiveDateTime,
+    pub label: String,
+    pub sessions: u32,
+    pub focus_secs: u64,
+}
+
+/// 将记录时长四舍五入到 `increment` 的整数倍；`increment` 为零时不处理
+pub fn round_secs(secs: u64, increment: Duration) -> u64 {
+    let step = increment.as_secs();
+    if step == 0 {
+        return secs;
+    }
+    (secs.saturating_add(step / 2) / step).saturating_mul(step)
+}
+
+/// 本地时间换算成带偏移的时刻：夏令时回拨时重复的一小时取较早的一次，
+/// 拨快时不存在的一小时按拨快前的偏移计算
+fn localize(at: NaiveDateTime) -> DateTime<FixedOffset> {
+    localize_in(&Local, at)
+}
+
+/// `localize` 的时区可以指定，测试时用固定规则的时区代替系统时区
+fn localize_in<Tz: TimeZone>(zone: &Tz, at: NaiveDateTime) -> DateTime<FixedOffset> {
+    match zone.from_local_datetime(&at) {
+        LocalResult::Single(time) => time.fixed_offset(),
+        // 两个候选的先后顺序不固定，按实际时刻取较早的
+        LocalResult::Ambiguous(a, b) => a.min(b).fixed_offset(),
+        LocalResult::None => {
+            let offset = zone
+                .from_local_datetime(&(at - TimeDelta::hours(1)))
+                .earliest()
+                .map_or_else(|| chrono::Utc.fix(), |before| before.offset().fix());
+            offset
+                .from_local_datetime(&at)
+                .single()
+                .unwrap_or_else(|| at.and_utc().fixed_offset())
+        }
+    }
+}
+
+/// 写入历史的时间格式，如 `2024-06-01T09:00:00+02:00`
+pub fn format_time(at: NaiveDateTime) -> String {
+    localize(at).to_rfc3339_opts(SecondsFormat::Secs, false)
+}
+
+/// 读取时统一换算到当前时区的本地时间，按天统计时都以本地日期为准
+/// 带时区的时间换算成 `zone` 的本地时间，旧格式原样使用
+fn parse_time_in<Tz: TimeZone>(zone: &Tz, text: &str) -> Result<NaiveDateTime> {
+    match DateTime::parse_from_rfc3339(text) {
+        Ok(time) => Ok(time.with_timezone(zone).naive_local()),
+        Err(_) => NaiveDateTime::parse_from_str(text, LEGACY_TIME_FORMAT)
+            .map_err(|_| anyhow!("invalid timestamp `{text}`")),
+    }
+}
+
+/// Default location: `<data dir>/pomodoros/history.csv`.
+pub fn default_path() -> Option<PathBuf> {
+    dirs::data_dir().map(|dir| dir.join("pomodoros").join("history.csv"))
+}
+
+/// 批次汇总写在历史文件旁的 `batches.csv`
+pub fn batch_path(history: &Path) -> PathBuf {
+    history.with_file_name("batches.csv")
+}
+
+/// 每天生效的每日目标，与历史文件放在一起
+pub fn goal_path(history: &Path) -> PathBuf {
+    history.with_file_name("goals.csv")
+}
+
+/// 记下某天生效的目标；当天已记过同样的目标时不写。同一天改过目标时以最后一行为准
+pub fn record_goal(path: &Path, day: NaiveDate, goal: u32) -> Result<()> {
+    if load_goals(path)?.get(&day) == Some(&goal) {
+        return Ok(());
+    }
+    append_line(path, GOAL_HEADER, &format!("{day},{goal}"))
+}
+
+pub fn load_goals(path: &Path) -> Result<BTreeMap<NaiveDate, u32>> {
+    if !path.exists() {
+        return Ok(BTreeMap::new());
+    }
+    let text = fs::read_to_string(path)
+        .with_context(|| format!("failed to read goal file {}", path.display()))?;
+    text.lines()
+        .enumerate()
+        .filter(|(_, line)| !line.trim().is_empty() && !line.starts_with("day,"))
+        .map(|(index, line)| {
+            line.split_once(',')
+                .and_then(|(day, goal)| Some((day.trim().parse().ok()?, goal.trim().parse().ok()?)))
+                .ok_or_else(|| anyhow!("{}:{}: malformed goal", path.display(), index + 1))
+        })
+        .collect()
+}
+
+pub fn append(path: &Path, record: &SessionRecord) -> Result<()> {
+    let line = format!(
+        "{},{},{},{},{},{},{},{}",
+        format_time(record.start),
+        format_time(record.end),
+        record.phase.key(),
+        record.duration_secs,
+        csv_field(record.batch.as_deref().unwrap_or("")),
+        record.interruptions,
+        csv_field(record.note.as_deref().unwrap_or("")),
+        record.completed
+    );
+    append_line(path, HEADER, &line)
+}
+
+pub fn append_batch(path: &Path, record: &BatchRecord) -> Result<()> {
+    let line = format!(
+        "{},{},{},{},{}",
+        format_time(record.start),
+        format_time(record.end),
+        csv_field(&record.label),
+        record.sessions,
+        record.focus_secs
+    );
+    append_line(path, BATCH_HEADER, &line)
+}
+
+fn append_line(path: &Path, header: &str, line: &str) -> Result<()> {
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent)
+            .with_context(|| format!("failed to create {}", parent.display()))?;
+    }
+    let is_new = !path.exists();
+    let mut file = OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(path)
+        .with_context(|| format!("failed to open history file {}", path.display()))?;
+    if is_new {
+        writeln!(file, "{header}")?;
+    }
+    writeln!(file, "{line}")?;
+    Ok(())
+}
+
+/// 含逗号、引号或换行的字段加引号
+pub fn csv_field(value: &str) -> String {
+    if value.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", value.replace('"', "\"\""))
+    } else {
+        value.to_string()
+    }
+}
+
+fn split_csv(line: &str) -> Vec<String> {
+    let mut fields = Vec::new();
+    let mut field = String::new();
+    let mut quoted = false;
+    let mut chars = line.chars().peekable();
+    while let Some(c) = chars.next() {
+        match c {
+            '"' if quoted && chars.peek() == Some(&'"') => {
+                field.push('"');
+                chars.next();
+            }
+            '"' => quoted = !quoted,
+            ',' if !quoted => fields.push(std::mem::take(&mut field)),
+            _ => field.push(c),
+        }
+    }
+    fields.push(field);
+    fields
+}
+
+/// 把旧版本不带时区的时间改写成 RFC 3339（历史文件和批次文件都处理），
+/// 原文件先备份为 `.bak`。返回改写的行数，没有旧记录时不动文件。
+pub fn migrate(path: &Path) -> Result<usize> {
+    let mut migrated = 0;
+    for path in [path.to_path_buf(), batch_path(path)] {
+        if !path.exists() {
+            continue;
+        }
+        let text = fs::read_to_string(&path)
+            .with_context(|| format!("failed to read history file {}", path.display()))?;
+        let mut changed = 0;
+        let mut out = String::with_capacity(text.len() + text.len() / 4);
+        for line in text.lines() {
+            match migrate_line(line) {
+                Some(line) => {
+                    out.push_str(&line);
+                    changed += 1;
+                }
+                None => out.push_str(line),
+            }
+            out.push('\n');
+        }
+        if changed == 0 {
+            continue;
+        }
+        let backup = path.with_extension("csv.bak");
+        fs::copy(&path, &backup)
+            .with_context(|| format!("failed to back up {}", path.display()))?;
+        // 先写临时文件再重命名，避免中途退出留下半个文件
+        let tmp = path.with_extension("csv.tmp");
+        fs::write(&tmp, out).with_context(|| format!("failed to write {}", tmp.display()))?;
+        fs::rename(&tmp, &path).with_context(|| format!("failed to write {}", path.display()))?;
+        migrated += changed;
+    }
+    Ok(migrated)
+}
+
+/// 前两列是旧格式时间的行改写后返回，其余（表头、新格式、空行）返回 `None`
+fn migrate_line(line: &str) -> Option<String> {
+    let mut fields = split_csv(line);
+    let [start, end, ..] = &fields[..] else {
+        return None;
+    };
+    let start = NaiveDateTime::parse_from_str(start, LEGACY_TIME_FORMAT).ok()?;
+    let end = NaiveDateTime::parse_from_str(end, LEGACY_TIME_FORMAT).ok()?;
+    fields[0] = format_time(start);
+    fields[1] = format_time(end);
+    let fields: Vec<String> = fields.iter().map(|field| csv_field(field)).collect();
+    Some(fields.join(","))
+}
+
+/// Read every record; a missing file is an empty history.
+pub fn load(path: &Path) -> Result<Vec<SessionRecord>> {
+    if !path.exists() {
+        return Ok(Vec::new());
+    }
+    let text = fs::read_to_string(path)
+        .with_context(|| format!("failed to read history file {}", path.display()))?;
+    text.lines()
+        .enumerate()
+        .filter(|(_, line)| !line.trim().is_empty() && !line.starts_with("start,"))
+        .map(|(index, line)| {
+            parse_line(line)
+                .with_context(|| format!("{}:{}: malformed record", path.display(), index + 1))
+        })
+        .collect()
+}
+
+fn parse_line(line: &str) -> Result<SessionRecord> {
+    parse_line_in(&Local, line)
+}
+
+/// `parse_line` 的时区可以指定，测试时用固定规则的时区代替系统时区
+pub(crate) fn parse_line_in<Tz: TimeZone>(zone: &Tz, line: &str) -> Result<SessionRecord> {
+    let fields = split_csv(line);
+    // 旧版本记录没有 batch 列，或没有 interruptions/note 列，或没有 completed 列
+    let (start, end, phase, duration_secs, batch, interruptions, note, completed) = match &fields[..]
+    {
+        [start, end, phase, duration_secs] => {
+            (start, end, phase, duration_secs, None, None, None, None)
+        }
+        [start, end, phase, duration_secs, batch] => (
+            start,
+            end,
+            phase,
+            duration_secs,
+            Some(batch),
+            None,
+            None,
+            None,
+        ),
+        [start, end, phase, duration_secs, batch, interruptions, note] => (
+            start,
+            end,
+            phase,
+            duration_secs,
+            Some(batch),
+            Some(interruptions),
+            Some(note),
+            None,
+        ),
+        [
+            start,
+            end,
+            phase,
+            duration_secs,
+            batch,
+            interruptions,
+            note,
+            completed,
+        ] => (
+            start,
+            end,
+            phase,
+            duration_secs,
+            Some(batch),
+            Some(interruptions),
+            Some(note),
+            Some(completed),
+        ),
+        _ => bail!("expected 4, 5, 7 or 8 fields, found {}", fields.len()),
+    };
+    Ok(SessionRecord {
+        start: parse_time_in(zone, start)?,
+        end: parse_time_in(zone, end)?,
+        phase: Phase::from_key(phase).ok_or_else(|| anyhow!("unknown phase `{phase}`"))?,
+        duration_secs: duration_secs.parse()?,
+        batch: batch.filter(|b| !b.is_empty()).cloned(),
+        interruptions: match interruptions {
+            Some(count) => count.parse()?,
+            None => 0,
+        },
+        note: note.filter(|n| !n.is_empty()).cloned(),
+        completed: match completed {
+            Some(flag) => flag.parse()?,
+            None => true,
+        },
+    })
+}
+
+#[cfg(test)]
+pub(crate) mod tests {
+    use super::*;
+
+    /// 2024 年的中欧时间：3 月 31 日 02:00 拨快到 03:00，10 月 27 日 03:00 拨回 02:00
+    #[derive(Debug, Clone, Copy)]
+    pub(crate) struct CentralEurope;
+
+    impl CentralEurope {
+        fn utc(month: u32, day: u32, hour: u32) -> NaiveDateTime {
+            NaiveDate::from_ymd_opt(2024, month, day)
+                .and_then(|date| date.and_hms_opt(hour, 0, 0))
+                .unwrap()
+        }
+
+        fn hours(hours: i32) -> FixedOffset {
+            FixedOffset::east_opt(hours * 3600).unwrap()
+        }
+    }
+
+    impl TimeZone for CentralEurope {
+        type Offset = FixedOffset;
+
+        fn from_offset(_offset: &FixedOffset) -> Self {
+            Self
+        }
+
+        fn offset_from_local_date(&self, local: &NaiveDate) -> LocalResult<FixedOffset> {
+            self.offset_from_local_datetime(&local.and_hms_opt(12, 0, 0).unwrap())
+        }
+
+        fn offset_from_local_datetime(&self, local: &NaiveDateTime) -> LocalResult<FixedOffset> {
+            let mut valid = [Self::hours(1), Self::hours(2)]
+                .into_iter()
+                .filter(|offset| {
+                    self.offset_from_utc_datetime(
+                        &(*local - TimeDelta::seconds(offset.local_minus_utc().into())),
+                    ) == *offset
+                });
+            match (valid.next(), valid.next()) {
+                (Some(a), Some(b)) => LocalResult::Ambiguous(a, b),
+                (Some(a), None) => LocalResult::Single(a),
+                _ => LocalResult::None,
+            }
+        }
+
+        fn offset_from_utc_date(&self, utc: &NaiveDate) -> FixedOffset {
+            self.offset_from_utc_datetime(&utc.and_hms_opt(0, 0, 0).unwrap())
+        }
+
+        fn offset_from_utc_datetime(&self, utc: &NaiveDateTime) -> FixedOffset {
+            let summer = Self::utc(3, 31, 1) <= *utc && *utc < Self::utc(10, 27, 1);
+            Self::hours(if summer { 2 } else { 1 })
+        }
+    }
+
+    fn local(text: &str) -> NaiveDateTime {
+        NaiveDateTime::parse_from_str(text, LEGACY_TIME_FORMAT).unwrap()
+    }
+
+    #[test]
+    fn localize_takes_the_earlier_offset_in_the_repeated_hour() {
+        let at = localize_in(&CentralEurope, local("2024-10-27 02:30:00"));
+        assert_eq!(at.offset(), &CentralEurope::hours(2));
+        assert_eq!(at.naive_utc(), local("2024-10-27 00:30:00"));
+        // 回拨之后的一小时照常
+        let after = localize_in(&CentralEurope, local("2024-10-27 03:30:00"));
+        assert_eq!(after.offset(), &CentralEurope::hours(1));
+    }
+
+    #[test]
+    fn localize_uses_the_offset_before_a_skipped_hour() {
+        let at = localize_in(&CentralEurope, local("2024-03-31 02:30:00"));
+        assert_eq!(at.offset(), &CentralEurope::hours(1));
+        assert_eq!(at.naive_local(), local("2024-03-31 02:30:00"));
+        let after = localize_in(&CentralEurope, local("2024-03-31 03:30:00"));
+        assert_eq!(after.offset(), &CentralEurope::hours(2));
+    }
+
+    #[test]
+    fn migrate_rewrites_legacy_times_and_keeps_a_backup() {
+        let dir = std::env::temp_dir().join(format!("pomodoros-migrate-{}", std::process::id()));
+        fs::create_dir_all(&dir).unwrap();
+        let path = dir.join("history.csv");
+        let legacy = "start,end,phase,duration_secs\n\
+                      2024-06-01 09:00:00,2024-06-01 09:25:00,focus,1500\n\
+                      2024-06-01 09:25:00,2024-06-01 09:30:00,short_break,300\n";
+        fs::write(&path, legacy).unwrap();
+
+        assert_eq!(migrate(&path).unwrap(), 2);
+        assert_eq!(
+            fs::read_to_string(path.with_extension("csv.bak")).unwrap(),
+            legacy
+        );
+        let migrated = fs::read_to_string(&path).unwrap();
+        for line in migrated.lines().skip(1) {
+            let start = line.split(',').next().unwrap();
+            assert!(DateTime::parse_from_rfc3339(start).is_ok(), "{line}");
+        }
+        let records = load(&path).unwrap();
+        assert_eq!(records[0].start, local("2024-06-01 09:00:00"));
+        assert_eq!(records[1].end, local("2024-06-01 09:30:00"));
+        assert_eq!(records[1].phase, Phase::ShortBreak);
+        // 再次迁移时没有旧格式的行，文件和备份都不动
+        assert_eq!(migrate(&path).unwrap(), 0);
+        assert_eq!(fs::read_to_string(&path).unwrap(), migrated);
+        fs::remove_dir_all(&dir).unwrap();
+    }
+}
@@ -0,0 +1,45 @@
+use std::sync::mpsc::Receiver;
+use std::time::Duration;
+
+/// 在后台线程轮询系统空闲时长，超过 `after` 与否变化时发送 `true`/`false`。
+/// 未启用 `idle` feature 或当前环境无法检测时返回 `None`。
+#[cfg(feature = "idle")]
+pub fn watch(after: Duration) -> Option<Receiver<bool>> {
+    use std::sync::mpsc;
+    use std::thread;
+
+    const POLL_INTERVAL: Duration = Duration::from_secs(1);
+
+    idle_time()?;
+    let (tx, rx) = mpsc::channel();
+    thread::spawn(move || {
+        let mut last = None;
+        loop {
+            // 查询失败时视为有活动，避免误暂停
+            let idle = idle_time().is_some_and(|time| time >= after);
+            if last != Some(idle) {
+                if tx.send(idle).is_err() {
+                    return;
+                }
+                last = Some(idle);
+            }
+            thread::sleep(POLL_INTERVAL);
+        }
+    });
+    Some(rx)
+}
+
+#[cfg(not(feature = "idle"))]
+pub fn watch(_after: Duration) -> Option<Receiver<bool>> {
+    None
+}
+
+#[cfg(feature = "idle")]
+fn idle_time() -> Option<Duration> {
+    // 没有 X 显示时 XScreenSaver 查询会直接崩溃
+    #[cfg(target_os = "linux")]
+    std::env::var_os("DISPLAY")?;
+    user_idle::UserIdle::get_time()
+        .ok()
+        .map(|idle| idle.duration())
+}
@@ -0,0 +1,55 @@
+use std::path::Path;
+
+use anyhow::{Result, bail};
+use serde::Serialize;
+
+use crate::history::{self, SessionRecord};
+
+/// 以 JSON 打印历史中的一条记录；`index` 从 1 开始按文件顺序计数，`None` 为最后一条
+pub fn run(path: &Path, index: Option<usize>) -> Result<()> {
+    let records = history::load(path)?;
+    let count = records.len();
+    if count == 0 {
+        bail!("no sessions recorded in {}", path.display());
+    }
+    let position = index.unwrap_or(count);
+    let Some(record) = position.checked_sub(1).and_then(|i| records.get(i)) else {
+        bail!("no session #{position}: the history has {count}");
+    };
+    println!(
+        "{}",
+        serde_json::to_string_pretty(&to_json(record, position))?
+    );
+    Ok(())
+}
+
+/// 按字段定义的顺序输出；时间用写入历史时的 RFC 3339 格式，`wall_secs` 是起止之间的实际时长，
+/// 与 `duration_secs`（不含暂停、可能已取整）对照即可看出差异来自哪里
+#[derive(Serialize)]
+struct Inspected<'a> {
+    index: usize,
+    start: String,
+    end: String,
+    phase: &'static str,
+    duration_secs: u64,
+    wall_secs: i64,
+    completed: bool,
+    interruptions: u32,
+    batch: Option<&'a str>,
+    note: Option<&'a str>,
+}
+
+fn to_json(record: &SessionRecord, position: usize) -> Inspected<'_> {
+    Inspected {
+        index: position,
+        start: history::format_time(record.start),
+        end: history::format_time(record.end),
+        phase: record.phase.key(),
+        duration_secs: record.duration_secs,
+        wall_secs: (record.end - record.start).num_seconds(),
+        completed: record.completed,
+        interruptions: record.interruptions,
+        batch: record.batch.as_deref(),
+        note: record.note.as_deref(),
+    }
+}
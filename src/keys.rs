@@ -0,0 +1,75 @@
+use anyhow::{Result, bail};
+use crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
+
+/// 配置中的按键，如 `q`、`esc`、`ctrl+q`、`f10`
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct KeySpec {
+    pub code: KeyCode,
+    pub ctrl: bool,
+}
+
+impl KeySpec {
+    pub fn parse(text: &str) -> Result<Self> {
+        let trimmed = text.trim();
+        let (ctrl, name) = match trimmed.get(..5) {
+            Some(prefix) if prefix.eq_ignore_ascii_case("ctrl+") => (true, &trimmed[5..]),
+            _ => (false, trimmed),
+        };
+        // 单个字符区分大小写，其余名称不区分
+        let lower = name.to_lowercase();
+        let code = match lower.as_str() {
+            "esc" | "escape" => KeyCode::Esc,
+            "enter" => KeyCode::Enter,
+            "tab" => KeyCode::Tab,
+            "backspace" => KeyCode::Backspace,
+            "space" => KeyCode::Char(' '),
+            _ if lower.starts_with('f') && lower.len() > 1 => match lower[1..].parse::<u8>() {
+                Ok(n) if (1..=12).contains(&n) => KeyCode::F(n),
+                _ => bail!("unknown key `{text}`"),
+            },
+            _ => {
+                let mut chars = name.chars();
+                match (chars.next(), chars.next()) {
+                    (Some(c), None) => KeyCode::Char(c),
+                    _ => bail!("unknown key `{text}`"),
+                }
+            }
+        };
+        Ok(Self { code, ctrl })
+    }
+
+    pub fn name(&self, ascii: bool) -> String {
+        let name = crate::actions::key_name(self.code, ascii);
+        if self.ctrl {
+            format!("Ctrl+{name}")
+        } else {
+            name
+        }
+    }
+
+    pub fn matches(&self, key: &KeyEvent) -> bool {
+        key.code == self.code && key.modifiers.contains(KeyModifiers::CONTROL) == self.ctrl
+    }
+}
+
+pub fn parse_all(names: &[String]) -> Result<Vec<KeySpec>> {
+    names.iter().map(|name| KeySpec::parse(name)).collect()
+}
+
+pub fn default_quit_keys() -> Vec<KeySpec> {
+    vec![
+        KeySpec {
+            code: KeyCode::Char('q'),
+            ctrl: false,
+        },
+        KeySpec {
+            code: KeyCode::Esc,
+            ctrl: false,
+        },
+    ]
+}
+
+/// Ctrl+C 始终退出，作为配置出错时的兜底
+pub fn is_interrupt(key: &KeyEvent) -> bool {
+    key.code == KeyCode::Char('c') && key.modifiers.contains(KeyModifiers::CONTROL)
+}
@@ -1,20 +1,68 @@
-use std::io::{self, Stdout};
-use std::time::{Duration, Instant};
+mod actions;
+mod alert;
+mod audio;
+mod battery;
+mod clock;
+mod config;
+mod db;
+mod dnd;
+mod durations;
+mod editor;
+mod export;
+mod foreground;
+mod graph;
+mod history;
+mod idle;
+mod inspect;
+mod keys;
+mod media;
+mod notify;
+mod numbers;
+mod observer;
+mod replay;
+mod report;
+mod ring;
+mod share;
+mod simulate;
+mod split_bar;
+mod state;
+mod stats;
+mod status;
+mod theme;
+mod timeline;
+mod wait;
 
-use anyhow::Result;
-use clap::{ArgAction, Parser};
+use std::collections::{BTreeSet, HashMap, VecDeque};
+use std::io::{self, IsTerminal, Stdout};
+use std::path::PathBuf;
+use std::process::ExitCode;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, mpsc};
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
+
+use anyhow::{Context, Result};
+use chrono::{Local, NaiveDate, NaiveDateTime, NaiveTime, TimeDelta, Utc};
+use clap::parser::ValueSource;
+use clap::{ArgAction, ArgMatches, CommandFactory, FromArgMatches, Parser, Subcommand};
 use crossterm::event::{
-    self, DisableMouseCapture, EnableMouseCapture, Event, KeyCode, KeyEventKind, KeyModifiers,
+    self, DisableBracketedPaste, DisableMouseCapture, EnableBracketedPaste, EnableMouseCapture,
+    Event, KeyCode, KeyEventKind, MouseButton, MouseEventKind,
 };
 use crossterm::execute;
 use crossterm::terminal::{
     EnterAlternateScreen, LeaveAlternateScreen, disable_raw_mode, enable_raw_mode,
 };
 use ratatui::Terminal;
-use ratatui::layout::{Alignment, Constraint, Direction, Layout};
+use ratatui::buffer::Buffer;
+use ratatui::layout::{Alignment, Constraint, Direction, Layout, Margin, Rect};
 use ratatui::style::{Color, Modifier, Style};
+use ratatui::symbols::border;
 use ratatui::text::{Line, Span};
+use ratatui::widgets::block::{Position, Title};
 use ratatui::widgets::{Block, BorderType, Borders, Clear, Gauge, Paragraph};
+use serde::{Deserialize, Serialize};
+
+use crate::clock::{Clock, SystemClock};
 
 /// 运行参数
 #[derive(Debug, Clone, Parser)]
@@ -32,20 +80,438 @@ struct CliArgs {
     #[arg(short = 'l', long = "long", default_value_t = 15)]
     long_break_minutes: u64,
 
+    /// Focus duration in seconds, overriding --focus (for quick tests and demos)
+    #[arg(long = "focus-seconds", value_name = "SECS", value_parser = clap::value_parser!(u64).range(1..))]
+    focus_seconds: Option<u64>,
+
+    /// Short break duration in seconds, overriding --short
+    #[arg(long = "short-seconds", value_name = "SECS", value_parser = clap::value_parser!(u64).range(1..))]
+    short_seconds: Option<u64>,
+
+    /// Long break duration in seconds, overriding --long
+    #[arg(long = "long-seconds", value_name = "SECS", value_parser = clap::value_parser!(u64).range(1..))]
+    long_seconds: Option<u64>,
+
+    /// Warn at startup when focus is less than R times the short break
+    #[arg(long = "min-focus-ratio", value_name = "R", default_value_t = 2.0, value_parser = parse_ratio)]
+    min_focus_ratio: f64,
+
+    /// Warn at startup when focus is more than R times the short break
+    #[arg(long = "max-focus-ratio", value_name = "R", default_value_t = 10.0, value_parser = parse_ratio)]
+    max_focus_ratio: f64,
+
+    /// Start without the focus:break ratio warning
+    #[arg(long = "force", default_value_t = false, action = ArgAction::SetTrue)]
+    force: bool,
+
     /// Take a long break after every N focus sessions
     #[arg(short = 'e', long = "every", default_value_t = 4)]
     long_every: u32,
 
+    /// Make the break after the first focus session a long one, then follow --every as usual
+    #[arg(long = "focus-first-break-long", default_value_t = false, action = ArgAction::SetTrue)]
+    focus_first_break_long: bool,
+
+    /// Begin in this phase instead of focus (also instead of the saved phase when resuming)
+    #[arg(long = "start-phase", value_enum, value_name = "PHASE")]
+    start_phase: Option<config::StartPhase>,
+
     /// Mute terminal bell
     #[arg(long = "mute", default_value_t = false, action = ArgAction::SetTrue)]
     mute: bool,
 
+    /// Flash the whole screen (inverted colors) a few times when a phase ends; works with or without --mute
+    /// (same as adding `flash` to --alert)
+    #[arg(long = "visual-bell", default_value_t = false, action = ArgAction::SetTrue)]
+    visual_bell: bool,
+
+    /// Comma-separated alerts to fire when a phase ends: bell, sound, notify, flash
+    /// (default: sound when --sound or --tone is set, otherwise bell)
+    #[arg(long = "alert", value_name = "LIST", value_enum, value_delimiter = ',')]
+    alert: Option<Vec<config::AlertBackend>>,
+
+    /// Play this audio file (wav/ogg/flac/mp3) instead of the terminal bell (needs the `sound` feature)
+    #[arg(long = "sound", value_name = "PATH")]
+    sound: Option<PathBuf>,
+
+    /// Ramp the custom sound's volume up over MS milliseconds (0 = start at full volume)
+    #[arg(long = "fade-in", value_name = "MS", default_value_t = 1000)]
+    fade_in_ms: u64,
+
+    /// Play a built-in tone instead of the terminal bell when no --sound file is set
+    /// (needs the `sound` feature)
+    #[arg(long = "tone", value_enum, value_name = "NAME")]
+    tone: Option<config::Tone>,
+
+    /// Volume of the sound file or tone, in percent
+    #[arg(long = "volume", value_name = "PERCENT", default_value_t = 100, value_parser = clap::value_parser!(u8).range(1..=100))]
+    volume: u8,
+
     /// Tick interval in milliseconds
     #[arg(long = "tick", default_value_t = 200)]
     tick_ms: u64,
+
+    /// Redraw at most N times a second; lower values cut flicker and bandwidth over slow SSH links
+    #[arg(long = "max-fps", value_name = "N", default_value_t = 60, value_parser = clap::value_parser!(u16).range(1..=240))]
+    max_fps: u16,
+
+    /// Show a "Get ready" countdown of N seconds before a focus phase auto-starts (0 = off)
+    #[arg(long = "ready-countdown", value_name = "SECONDS", default_value_t = 0)]
+    ready_countdown_secs: u64,
+
+    /// Show a "Settle in" countdown of N seconds at launch, then start the first focus (0 = off)
+    #[arg(long = "warmup", value_name = "SECONDS", default_value_t = 0)]
+    warmup_secs: u64,
+
+    /// Disable the highlighted border during the final minute of a phase and the blinking
+    /// digits in the last 5 seconds
+    #[arg(long = "no-final-warning", default_value_t = false, action = ArgAction::SetTrue)]
+    no_final_warning: bool,
+
+    /// Pause the timer with a warning when the battery drops below PERCENT
+    #[arg(long = "pause-on-low-battery", value_name = "PERCENT", value_parser = clap::value_parser!(u8).range(1..=100))]
+    pause_on_low_battery: Option<u8>,
+
+    /// Pause focus when the default audio output changes, e.g. headphones unplugged
+    /// (macOS, needs the `audio-change` feature)
+    #[arg(long = "pause-on-audio-change", default_value_t = false, action = ArgAction::SetTrue)]
+    pause_on_audio_change: bool,
+
+    /// Pause focus after SECONDS without keyboard/mouse input anywhere on the system,
+    /// resuming on activity (needs the `idle` feature)
+    #[arg(long = "auto-pause-idle", value_name = "SECONDS", value_parser = clap::value_parser!(u64).range(1..))]
+    auto_pause_idle_secs: Option<u64>,
+
+    /// Keep one desktop notification showing the remaining time, updated every minute
+    /// (Linux notify-send; elsewhere a notification per phase)
+    #[arg(long = "live-notify", default_value_t = false, action = ArgAction::SetTrue)]
+    live_notify: bool,
+
+    /// Pause media players during breaks and resume them for focus (MPRIS / AppleScript)
+    #[arg(long = "media-control", default_value_t = false, action = ArgAction::SetTrue)]
+    media_control: bool,
+
+    /// With --media-control, also pause media while a focus session is paused
+    #[arg(long = "media-follow-pause", default_value_t = false, action = ArgAction::SetTrue)]
+    media_follow_pause: bool,
+
+    /// Turn on the OS do-not-disturb mode once a focus session starts and off for breaks
+    /// (Linux: GNOME via gsettings; macOS: Shortcuts named "pomodoros dnd on/off")
+    #[arg(long = "dnd", default_value_t = false, action = ArgAction::SetTrue)]
+    dnd: bool,
+
+    /// Shell command that turns do-not-disturb on for --dnd, replacing the platform default
+    #[arg(long = "dnd-on-cmd", value_name = "CMD")]
+    dnd_on_cmd: Option<String>,
+
+    /// Shell command that turns do-not-disturb off again
+    #[arg(long = "dnd-off-cmd", value_name = "CMD")]
+    dnd_off_cmd: Option<String>,
+
+    /// Keep a phase's remaining time when switching away with 1/2/3, and pick it up again on return
+    #[arg(long = "resume-remaining", default_value_t = false, action = ArgAction::SetTrue)]
+    resume_remaining: bool,
+
+    /// Start the focus session after a long break paused (short breaks still continue on their own)
+    #[arg(long = "pause-after-long", default_value_t = false, action = ArgAction::SetTrue)]
+    pause_after_long: bool,
+
+    /// Keep counting past 00:00 in focus (shown as +MM:SS) until you press `n`,
+    /// which logs the full length including overtime
+    #[arg(long = "overrun", default_value_t = false, action = ArgAction::SetTrue)]
+    overrun: bool,
+
+    /// When a phase ends, keep ringing every second and hold the next phase until a key is pressed
+    #[arg(long = "alert-until-ack", default_value_t = false, action = ArgAction::SetTrue)]
+    alert_until_ack: bool,
+
+    /// Read each focus session's length from this file or FIFO, one line per session
+    /// (e.g. `40` or `1h`); empty or unreadable lines use --focus
+    #[arg(long = "duration-source", value_name = "PATH")]
+    duration_source: Option<PathBuf>,
+
+    /// Don't allow skipping a break during its first SECONDS
+    #[arg(long = "min-break", value_name = "SECONDS", default_value_t = 0)]
+    min_break_secs: u64,
+
+    /// Skip a focus session only when `n` is pressed twice within MS milliseconds
+    /// (0 = a single press skips)
+    #[arg(long = "confirm-skip", value_name = "MS", default_value_t = 0)]
+    confirm_skip_ms: u64,
+
+    /// Name shown for focus phases (header, notifications, timeline), e.g. "Write"
+    #[arg(long = "focus-name", value_name = "NAME")]
+    focus_name: Option<String>,
+
+    /// Name shown for short breaks
+    #[arg(long = "short-name", value_name = "NAME")]
+    short_name: Option<String>,
+
+    /// Name shown for long breaks
+    #[arg(long = "long-name", value_name = "NAME")]
+    long_name: Option<String>,
+
+    /// Locale for thousands separators in stats, summary and report output, e.g. de_DE
+    /// (default: LC_ALL / LC_NUMERIC / LANG)
+    #[arg(long = "locale", value_name = "LOCALE")]
+    locale: Option<String>,
+
+    /// Draw icons with ASCII characters only, for terminals without good glyph support
+    #[arg(long = "ascii", default_value_t = false, action = ArgAction::SetTrue)]
+    ascii: bool,
+
+    /// When a paused timer is set to 00:00: `finish` ends the phase right away
+    /// (the next one stays paused), `hold` waits at 00:00 until resumed
+    #[arg(long = "zero-behavior", value_enum, default_value_t = config::ZeroBehavior::Hold)]
+    zero_behavior: config::ZeroBehavior,
+
+    /// Phase colors: `default`, or `colorblind` (orange / sky blue / blue, plus a distinct
+    /// border and icon per phase)
+    #[arg(long = "palette", value_enum, default_value_t = config::Palette::Default)]
+    palette: config::Palette,
+
+    /// Load every UI color from a TOML or JSON file (keys like `focus`, `gauge_bg`, `warning`),
+    /// on top of --palette
+    #[arg(long = "theme-file", value_name = "PATH")]
+    theme_file: Option<PathBuf>,
+
+    /// How to draw the progress panel
+    #[arg(long = "bar-style", value_enum, default_value_t = config::BarStyle::Gauge)]
+    bar_style: config::BarStyle,
+
+    /// Whether the gauge fills up or drains as time passes
+    #[arg(long = "gauge-direction", value_enum, default_value_t = config::GaugeDirection::Fill)]
+    gauge_direction: config::GaugeDirection,
+
+    /// What the header's Completed number counts: this run, today or this week (from the history)
+    #[arg(long = "count-scope", value_name = "SCOPE", value_enum, default_value_t = config::CountScope::Run)]
+    count_scope: config::CountScope,
+
+    /// Draw the progress as a ring around the big timer instead of the progress panel
+    /// (falls back to the panel when the terminal is too small, and with --ascii)
+    #[arg(long = "ring", default_value_t = false, action = ArgAction::SetTrue)]
+    ring: bool,
+
+    /// Tint the whole background per phase (green for focus, blue for breaks); needs a
+    /// truecolor terminal and does nothing elsewhere
+    #[arg(long = "ambient-bg", default_value_t = false, action = ArgAction::SetTrue)]
+    ambient_bg: bool,
+
+    /// Don't show today's summary when the timer starts
+    #[arg(long = "no-splash", default_value_t = false, action = ArgAction::SetTrue)]
+    no_splash: bool,
+
+    /// Exit as soon as --max-sessions or --until is reached instead of waiting on a summary screen
+    #[arg(long = "no-done-screen", default_value_t = false, action = ArgAction::SetTrue)]
+    no_done_screen: bool,
+
+    /// Don't play the transition animation when a phase ends, and keep the paused indicator still
+    #[arg(long = "no-animations", default_value_t = false, action = ArgAction::SetTrue)]
+    no_animations: bool,
+
+    /// Quit after MIN minutes without key presses or completed sessions (0 = off)
+    #[arg(long = "auto-quit-idle", value_name = "MIN", default_value_t = 0)]
+    auto_quit_idle_minutes: u64,
+
+    /// Don't capture the mouse, so the terminal's own text selection works (disables click-to-toggle)
+    #[arg(long = "no-mouse", default_value_t = false, action = ArgAction::SetTrue)]
+    no_mouse: bool,
+
+    /// Draw in the main screen buffer instead of the alternate screen, so scrollback is kept
+    /// (for logging, or terminals/tmux setups where the alternate screen misbehaves)
+    #[arg(long = "no-alternate-screen", default_value_t = false, action = ArgAction::SetTrue)]
+    no_alternate_screen: bool,
+
+    /// Start as if N focus sessions were already done, so the long-break cadence continues from there
+    /// (overrides the count restored from the state file)
+    #[arg(long = "completed", value_name = "N")]
+    completed: Option<u32>,
+
+    /// Exit after N completed focus sessions
+    #[arg(long = "max-sessions", value_name = "N", conflicts_with = "once")]
+    max_sessions: Option<u32>,
+
+    /// Run a work block of N focus sessions with breaks between, then stop with a summary
+    /// (w starts a new block)
+    #[arg(long = "block", value_name = "N", value_parser = clap::value_parser!(u32).range(1..))]
+    block: Option<u32>,
+
+    /// Exit after a single focus session (same as --max-sessions 1)
+    #[arg(long = "once", default_value_t = false, action = ArgAction::SetTrue)]
+    once: bool,
+
+    /// Keep cycling until this local time, then finish the current phase and exit
+    /// (a time already past today means tomorrow)
+    #[arg(long = "until", value_name = "HH:MM", value_parser = parse_time_of_day)]
+    until: Option<NaiveTime>,
+
+    /// Round each logged session duration to the nearest N minutes (0 = no rounding)
+    #[arg(long = "round-log", value_name = "MIN", default_value_t = 0)]
+    round_log_minutes: u64,
+
+    /// Daily goal of completed focus sessions
+    #[arg(long = "daily-goal", value_name = "N", global = true)]
+    daily_goal: Option<u32>,
+
+    /// Carry shortfalls against the daily goal forward as "debt" (see `stats`)
+    #[arg(long = "track-debt", default_value_t = false, action = ArgAction::SetTrue, global = true)]
+    track_debt: bool,
+
+    /// Show the current local time (HH:MM) in the status panel
+    #[arg(long = "show-clock", default_value_t = false, action = ArgAction::SetTrue)]
+    show_clock: bool,
+
+    /// Hide the remaining time during focus, showing only "Focusing…" and a bar without numbers
+    /// (v toggles it)
+    #[arg(long = "hide-time", default_value_t = false, action = ArgAction::SetTrue)]
+    hide_time: bool,
+
+    /// Only count focus time while the named process owns the foreground window
+    #[arg(long = "pause-unless-process", value_name = "NAME")]
+    pause_unless_process: Option<String>,
+
+    /// Config file path (default: <config dir>/pomodoros/config.toml)
+    #[arg(short = 'c', long = "config", value_name = "PATH", global = true)]
+    config: Option<PathBuf>,
+
+    /// Session history file (default: <data dir>/pomodoros/history.csv)
+    #[arg(long = "history", value_name = "PATH", global = true)]
+    history: Option<PathBuf>,
+
+    /// Also insert every finished phase into this SQLite database (needs the `sqlite` feature)
+    #[arg(long = "db", value_name = "PATH")]
+    db: Option<PathBuf>,
+
+    /// State file for resume, daily count and streak (default: <data dir>/pomodoros/state.json)
+    #[arg(long = "state-file", value_name = "PATH", global = true)]
+    state_file: Option<PathBuf>,
+
+    /// Start a fresh timer instead of resuming the saved one
+    #[arg(long = "fresh", default_value_t = false, action = ArgAction::SetTrue)]
+    fresh: bool,
+
+    /// Print the config file and counters as one base64 string (for copying to another machine)
+    #[arg(long = "export-state", default_value_t = false, action = ArgAction::SetTrue, conflicts_with = "import_state")]
+    export_state: bool,
+
+    /// Replace the config file and counters with a string from --export-state
+    #[arg(long = "import-state", value_name = "STRING")]
+    import_state: Option<String>,
+
+    #[command(subcommand)]
+    command: Option<Command>,
 }
 
-#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[derive(Debug, Clone, Subcommand)]
+enum Command {
+    /// Print a day's phases from history as a timeline
+    Timeline {
+        /// Day to show, YYYY-MM-DD (default: today)
+        #[arg(long = "date")]
+        date: Option<NaiveDate>,
+
+        /// Gaps of at least this many minutes are marked as a break from work
+        #[arg(long = "gap", value_name = "MIN", default_value_t = 30)]
+        gap_minutes: u64,
+    },
+    /// Print today's numbers, goal progress, debt and lifetime totals
+    Stats,
+    /// Print a bar chart of focused time per day
+    Graph {
+        /// Number of days to show, ending today
+        #[arg(long = "days", default_value_t = 14, value_parser = clap::value_parser!(u32).range(1..))]
+        days: u32,
+    },
+    /// Forget the accumulated goal debt; counting starts again today
+    ResetDebt,
+    /// Print the running timer's status as one line (for status bars) and exit
+    Status {
+        /// Placeholders: {phase} {phase_key} {time} {elapsed} {percent} {state} {completed} {today}
+        #[arg(long = "format", default_value = "{phase} {time}")]
+        format: String,
+    },
+    /// Export a day's focus sessions (grouped by batch label) for standups
+    Report {
+        #[arg(long = "format", value_enum, default_value_t = report::ReportFormat::Md)]
+        format: report::ReportFormat,
+
+        /// today, yesterday or YYYY-MM-DD
+        #[arg(long = "date", default_value = "today", value_parser = report::parse_day)]
+        date: NaiveDate,
+
+        /// Write to PATH instead of stdout
+        #[arg(long = "out", value_name = "PATH")]
+        out: Option<PathBuf>,
+    },
+    /// List a day's sessions in order with their times, labels and notes, plus a total
+    Replay {
+        /// today, yesterday or YYYY-MM-DD
+        #[arg(default_value = "today", value_parser = report::parse_day)]
+        date: NaiveDate,
+    },
+    /// Print one history record as JSON (the most recent by default), to check what was logged
+    Inspect {
+        /// Print the latest record (the default)
+        #[arg(long = "last", default_value_t = false, action = ArgAction::SetTrue, conflicts_with = "index")]
+        last: bool,
+
+        /// Print the Nth record in file order, starting at 1
+        #[arg(long = "index", value_name = "N", value_parser = clap::value_parser!(u64).range(1..))]
+        index: Option<u64>,
+    },
+    /// Export history as CSV for a time tracker's importer (focus is billable, breaks are not)
+    Export {
+        #[arg(long = "format", value_enum, default_value_t = export::ExportFormat::Toggl)]
+        format: export::ExportFormat,
+
+        /// First day to include: today, yesterday or YYYY-MM-DD (default: all history)
+        #[arg(long = "from", value_parser = report::parse_day)]
+        from: Option<NaiveDate>,
+
+        /// Last day to include (default: all history)
+        #[arg(long = "to", value_parser = report::parse_day)]
+        to: Option<NaiveDate>,
+
+        /// Your account email, which Toggl's importer requires on every row
+        #[arg(long = "email", value_name = "ADDRESS")]
+        email: String,
+
+        /// Write to PATH instead of stdout
+        #[arg(long = "out", value_name = "PATH")]
+        out: Option<PathBuf>,
+    },
+    /// Print a recap of a day: focus time, sessions, interruptions and goal progress
+    Summary {
+        /// Day to summarize, YYYY-MM-DD (default: today)
+        #[arg(long = "date")]
+        date: Option<NaiveDate>,
+    },
+    /// Fast-forward the timer through HOURS of simulated time and check it behaves
+    /// (phase order, long-break cadence, counters); exits non-zero on the first violation
+    Simulate {
+        #[arg(long = "hours", default_value_t = 24)]
+        hours: u64,
+
+        /// Simulated time per tick
+        #[arg(long = "step-ms", default_value_t = 200)]
+        step_ms: u64,
+    },
+    /// Run one focus countdown without the TUI and exit 0 when it's done (for scripts)
+    Wait {
+        /// Length, e.g. 25m, 90s, 1h30m (a bare number is minutes)
+        #[arg(long = "focus", default_value = "25m", value_parser = parse_duration)]
+        focus: Duration,
+
+        /// Don't print the countdown
+        #[arg(short = 'q', long = "quiet", default_value_t = false, action = ArgAction::SetTrue)]
+        quiet: bool,
+    },
+    /// Fire each configured alert once and exit (to check the bell works)
+    TestBell,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
 enum Phase {
     Focus,
     ShortBreak,
@@ -61,321 +527,4139 @@ impl Phase {
         }
     }
 
-    fn color(self) -> Color {
+    /// 历史记录中使用的标识
+    fn key(self) -> &'static str {
+        match self {
+            Phase::Focus => "focus",
+            Phase::ShortBreak => "short_break",
+            Phase::LongBreak => "long_break",
+        }
+    }
+
+    fn from_key(key: &str) -> Option<Self> {
+        match key {
+            "focus" => Some(Phase::Focus),
+            "short_break" => Some(Phase::ShortBreak),
+            "long_break" => Some(Phase::LongBreak),
+            _ => None,
+        }
+    }
+
+    /// `--ambient-bg` 的整屏底色，足够暗以保证文字对比度
+    fn tint(self) -> Color {
+        match self {
+            Phase::Focus => Color::Rgb(12, 32, 18),
+            Phase::ShortBreak => Color::Rgb(10, 24, 40),
+            Phase::LongBreak => Color::Rgb(14, 18, 48),
+        }
+    }
+}
+
+/// 页脚中的单行输入框
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum PromptKind {
+    GoTo,
+    BatchLabel,
+    Note,
+    /// `c`：不计时直接记一个已完成的专注
+    LogSession,
+    /// `g g` 的确认：y 确认，其他键取消
+    ResetCount,
+}
+
+impl PromptKind {
+    fn label(self) -> &'static str {
         match self {
-            Phase::Focus => Color::LightGreen,
-            Phase::ShortBreak => Color::Cyan,
-            Phase::LongBreak => Color::Magenta,
+            PromptKind::GoTo => "Go to (MM:SS)",
+            PromptKind::BatchLabel => "Batch label",
+            PromptKind::Note => "Note",
+            PromptKind::LogSession => "Log a focus session (e.g. 25m, empty = focus length)",
+            PromptKind::ResetCount => "Reset the completed count to 0 (y/n)",
+        }
+    }
+}
+
+#[derive(Debug)]
+struct Prompt {
+    kind: PromptKind,
+    input: String,
+    error: Option<&'static str>,
+}
+
+/// 阶段切换动画的时长
+const TRANSITION_DURATION: Duration = Duration::from_millis(400);
+/// 视觉铃：反色与正常各持续 `FLASH_STEP`，共闪 `FLASH_COUNT` 次
+const FLASH_STEP: Duration = Duration::from_millis(120);
+const FLASH_COUNT: u32 = 3;
+/// 暂停提示在正常与变暗之间切换的间隔
+const PAUSE_PULSE: Duration = Duration::from_millis(800);
+
+/// 数字开始闪烁的剩余时间，以及闪烁的节拍
+const FINAL_SECONDS: Duration = Duration::from_secs(5);
+const FINAL_BLINK: Duration = Duration::from_millis(500);
+
+/// 页脚提示的显示时长
+const NOTICE_DURATION: Duration = Duration::from_secs(8);
+
+/// 帮助面板翻页的行数
+const HELP_PAGE: u16 = 10;
+/// `--alert-until-ack` 下重复提醒的间隔
+const ALERT_REPEAT: Duration = Duration::from_secs(1);
+/// 启动概况的显示时长，按任意键提前关闭
+const SPLASH_DURATION: Duration = Duration::from_millis(2500);
+
+/// 带标签的一组专注会话
+#[derive(Debug)]
+struct Batch {
+    label: String,
+    started: NaiveDateTime,
+    sessions: u32,
+    focus: Duration,
+}
+
+/// `--block` 的一组专注，进度与累计的完成数分开统计
+#[derive(Debug, Clone, Copy)]
+struct WorkBlock {
+    started: NaiveDateTime,
+    sessions: u32,
+    focus: Duration,
+    interruptions: u32,
+    /// 完成最后一个专注的时间；完成后计时停下并显示汇总
+    finished: Option<NaiveDateTime>,
+}
+
+impl WorkBlock {
+    fn new(started: NaiveDateTime) -> Self {
+        Self {
+            started,
+            sessions: 0,
+            focus: Duration::ZERO,
+            interruptions: 0,
+            finished: None,
         }
     }
 }
 
+/// 一组阶段时长（`[preset.a]` / `[preset.b]`）
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct Preset {
+    focus: Duration,
+    short_break: Duration,
+    long_break: Duration,
+}
+
 #[derive(Debug, Clone)]
 struct PomodoroConfig {
     focus: Duration,
     short_break: Duration,
     long_break: Duration,
+    /// `p` 键在这两组时长之间切换；配置文件没有 `[preset]` 时为 `None`
+    presets: Option<[Preset; 2]>,
+    /// `[profiles]` 里的各组时长，按名称排序
+    profiles: Vec<(String, Preset)>,
     long_every: u32,
+    /// 第一个专注之后总是长休息
+    first_break_long: bool,
+    /// `--start-phase`；`None` 时从专注开始，或恢复保存的阶段
+    start_phase: Option<Phase>,
     mute: bool,
+    /// 到点时使用的提醒方式（`--alert`）；`mute` 只压下响铃和提示音
+    alerts: BTreeSet<config::AlertBackend>,
+    /// 自定义提示音，未设置时响终端铃
+    sound: Option<PathBuf>,
+    fade_in: Duration,
+    /// 没有声音文件时播放的内置音色
+    tone: Option<config::Tone>,
+    /// 音量百分比（1..=100）
+    volume: u8,
+    ready_countdown: Duration,
+    /// 启动时第一次专注前的热身时长
+    warmup: Duration,
+    final_warning: bool,
+    messages: Vec<String>,
+    message_order: config::MessageOrder,
+    pause_unless_process: Option<String>,
+    pause_on_low_battery: Option<u8>,
+    pause_on_audio_change: bool,
+    auto_pause_idle: Option<Duration>,
+    max_sessions: Option<u32>,
+    /// `--block` 每组的专注数
+    block: Option<u32>,
+    /// `--until` 的截止时刻（本地时间），启动时已换算成具体日期
+    until: Option<NaiveDateTime>,
+    /// 预设已完成的专注数（`--completed`）
+    completed: Option<u32>,
+    quit_keys: Vec<keys::KeySpec>,
+    media_control: bool,
+    /// 专注中途暂停时也暂停媒体
+    media_follow_pause: bool,
+    /// 专注时开启勿扰；prepare 之后两条命令都已确定
+    dnd: bool,
+    dnd_on_cmd: Option<String>,
+    dnd_off_cmd: Option<String>,
+    /// 常驻通知显示剩余时间
+    live_notify: bool,
+    /// 捕获鼠标（单击切换开始/暂停）
+    mouse: bool,
+    /// 使用备用屏幕缓冲区
+    alternate_screen: bool,
+    /// 无操作自动退出的时长，零表示关闭
+    auto_quit_idle: Duration,
+    /// 启动时显示今天的概况
+    splash: bool,
+    /// 计划完成后停在汇总画面，按任意键才退出
+    done_screen: bool,
+    animations: bool,
+    /// 按阶段给整个屏幕染上底色
+    ambient_bg: bool,
+    /// 用 1/2/3 切走的阶段保留剩余时间，切回来时继续
+    resume_remaining: bool,
+    /// 长休息结束后不自动开始专注
+    pause_after_long: bool,
+    /// 专注到点后继续计时，手动结束
+    overrun: bool,
+    /// 到点后持续提醒，按键确认后才开始下一阶段
+    alert_until_ack: bool,
+    /// 休息开始后这段时间内不能跳过
+    min_break: Duration,
+    /// 跳过专注时两次按 `n` 的最长间隔，零表示按一次就跳过
+    confirm_skip: Duration,
+    zero_behavior: config::ZeroBehavior,
+    phase_styles: config::PhaseStyles,
+    icons: config::Icons,
+    names: config::PhaseNames,
+    numbers: numbers::NumberFormat,
+    palette: config::Palette,
+    /// 界面颜色（`--palette` 加上 `--theme-file`）
+    theme: theme::Theme,
+    bar_style: config::BarStyle,
+    gauge_direction: config::GaugeDirection,
+    /// 表头 Completed 的统计范围
+    count_scope: config::CountScope,
+    /// 用圆环代替进度面板
+    ring: bool,
+    show_clock: bool,
+    /// 专注时不显示剩余时间（`v` 切换）
+    hide_time: bool,
+    round_log: Duration,
+    daily_goal: Option<u32>,
+}
+
+/// `--resume-remaining` 下切走时保存的阶段进度
+#[derive(Debug)]
+struct Stashed {
+    total: Duration,
+    remaining: Duration,
+    started: NaiveDateTime,
+    interruptions: u32,
+    note: Option<String>,
+    logged: Duration,
+    ran: Duration,
+}
+
+impl PomodoroConfig {
+    fn cue(&self) -> alert::Cue {
+        alert::Cue {
+            sound: self.sound.clone(),
+            tone: self.tone,
+            fade_in: self.fade_in,
+            volume: f32::from(self.volume) / 100.0,
+        }
+    }
+}
+
+/// 到点后等待确认的提醒
+#[derive(Debug)]
+struct Alerting {
+    last_ring: Instant,
+    /// 确认后是否开始下一阶段（本来就会自动开始时才开始）
+    start: bool,
 }
 
 #[derive(Debug)]
 struct PomodoroApp {
     config: PomodoroConfig,
+    clock: Box<dyn Clock>,
     phase: Phase,
     total: Duration,
     remaining: Duration,
     running: bool,
+    /// 到点完成的专注数，长休息节奏只按它计算
     completed_focus: u32,
+    /// 本次运行中按 `n` 跳过的专注数，不写入历史也不影响长休息节奏
+    skipped_focus: u32,
     last_tick: Instant,
+    /// 专注阶段已就绪、等待自动开始的截止时间
+    ready_until: Option<Instant>,
+    /// 当前的就绪倒计时是启动时的热身（`--warmup`）
+    warming_up: bool,
+    /// 当前专注阶段展示的激励语下标
+    message: Option<usize>,
+    next_message: usize,
+    /// 当前阶段首次开始计时的本地时间
+    phase_started: Option<NaiveDateTime>,
+    /// 待写入历史文件的记录
+    pending_records: Vec<history::SessionRecord>,
+    prompt: Option<Prompt>,
+    /// 帮助面板的滚动位置，`None` 表示未打开
+    help: Option<u16>,
+    /// 目标进程是否在前台
+    foreground_ok: bool,
+    /// 因目标进程不在前台而自动暂停
+    auto_paused: bool,
+    /// 系统空闲超过 `--auto-pause-idle`
+    user_idle: bool,
+    /// 因系统空闲而自动暂停
+    idle_paused: bool,
+    /// 本次运行开始时已完成的专注数（`--max-sessions` 只统计本次运行）
+    run_baseline: u32,
+    day: NaiveDate,
+    day_completed: u32,
+    /// 本周完成的专注（`--count-scope week`），启动时从历史记录统计
+    week_completed: u32,
+    /// 当天专注中途暂停或跳过的次数
+    day_interruptions: u32,
+    /// 当前阶段被暂停的次数，写入历史记录
+    phase_interruptions: u32,
+    /// 当前阶段的备注
+    phase_note: Option<String>,
+    /// 当前阶段已作为未完成记录写入历史的时长
+    logged: Duration,
+    /// 当前阶段实际计时的时长（含超时、不含暂停），写入历史的就是它；
+    /// 用 `g` 等改动剩余时间不会让它变多
+    ran: Duration,
+    /// 上次按下跳过的时间（`--confirm-skip` 的第一次按键）
+    last_skip_press: Option<Instant>,
+    /// 用 1/2/3 切走时保存的各阶段进度（`--resume-remaining`）
+    stashed: HashMap<Phase, Stashed>,
+    /// `--duration-source` 读到、尚未使用的专注时长；`None` 表示用默认时长
+    queued_focus: VecDeque<Option<Duration>>,
+    streak: u32,
+    debt_reset: Option<NaiveDate>,
+    /// 低电量警告弹窗显示中（显示当时的电量）
+    battery_alert: Option<u8>,
+    /// 已因低电量暂停过，充电回到阈值以上前不再重复触发
+    battery_tripped: bool,
+    batch: Option<Batch>,
+    pending_batches: Vec<history::BatchRecord>,
+    work_block: Option<WorkBlock>,
+    notice: Option<(String, Instant)>,
+    /// 最近一次按键或完成阶段的时间
+    last_activity: Instant,
+    /// 阶段切换动画的开始时间
+    transition: Option<Instant>,
+    /// 视觉铃的开始时间
+    flash: Option<Instant>,
+    /// 已按下、等待第二个键的组合键
+    pending_chord: Option<(KeyCode, Instant)>,
+    /// `--overrun` 下专注到点后的超时时长
+    overtime: Option<Duration>,
+    /// 等待确认的到点提醒（`--alert-until-ack`）
+    alerting: Option<Alerting>,
+    /// 已过 `--until` 截止时刻且当前阶段已结束
+    workday_over: bool,
+    /// 启动时的今日概况及其关闭时间
+    splash: Option<(stats::DaySummary, Instant)>,
+    /// 本次运行实际计时的专注与休息时长
+    focused_total: Duration,
+    break_total: Duration,
+    /// 当前使用的时长预设下标（0 = A，1 = B），时长与两组都不同时为 `None`
+    preset: Option<usize>,
+    /// 当前使用的 `[profiles]` 项下标
+    profile: Option<usize>,
+    /// 按下了编辑配置键，由主循环挂起界面后处理
+    edit_requested: bool,
+    observers: Vec<Box<dyn observer::PomodoroObserver>>,
+    /// 上次通知观察者时的运行状态，用于发出暂停/继续事件
+    observed_running: bool,
 }
 
 impl PomodoroApp {
     fn new(config: PomodoroConfig) -> Self {
+        Self::with_clock(config, Box::new(SystemClock))
+    }
+
+    fn with_clock(config: PomodoroConfig, clock: Box<dyn Clock>) -> Self {
         let total = config.focus;
-        Self {
+        let completed = config.completed.unwrap_or(0);
+        let now = clock.now();
+        let today = clock.local().date();
+        let phase = config.start_phase.unwrap_or(Phase::Focus);
+        // 热身只在启动时出现一次，不计入专注时间；从休息开始时不需要
+        let warmup_until = (!config.warmup.is_zero() && phase == Phase::Focus)
+            .then(|| now.checked_add(config.warmup))
+            .flatten();
+        let work_block = config.block.map(|_| WorkBlock::new(clock.local()));
+        let mut app = Self {
             config,
-            phase: Phase::Focus,
+            clock,
+            phase,
             total,
             remaining: total,
             running: false,
-            completed_focus: 0,
-            last_tick: Instant::now(),
+            completed_focus: completed,
+            skipped_focus: 0,
+            last_tick: now,
+            ready_until: warmup_until,
+            warming_up: warmup_until.is_some(),
+            message: None,
+            next_message: 0,
+            phase_started: None,
+            pending_records: Vec::new(),
+            prompt: None,
+            help: None,
+            foreground_ok: true,
+            auto_paused: false,
+            user_idle: false,
+            idle_paused: false,
+            run_baseline: completed,
+            day: today,
+            day_completed: 0,
+            week_completed: 0,
+            day_interruptions: 0,
+            phase_interruptions: 0,
+            logged: Duration::ZERO,
+            ran: Duration::ZERO,
+            queued_focus: VecDeque::new(),
+            stashed: HashMap::new(),
+            last_skip_press: None,
+            phase_note: None,
+            streak: 0,
+            debt_reset: None,
+            battery_alert: None,
+            battery_tripped: false,
+            batch: None,
+            pending_batches: Vec::new(),
+            work_block,
+            notice: None,
+            last_activity: now,
+            transition: None,
+            flash: None,
+            pending_chord: None,
+            overtime: None,
+            alerting: None,
+            workday_over: false,
+            splash: None,
+            focused_total: Duration::ZERO,
+            break_total: Duration::ZERO,
+            preset: None,
+            profile: None,
+            edit_requested: false,
+            observers: Vec::new(),
+            observed_running: false,
+        };
+        if phase != Phase::Focus {
+            app.reset_current();
         }
+        app.pick_message();
+        app.preset = app.matching_preset();
+        app.profile = app.matching_profile();
+        app
     }
 
-    fn reset_current(&mut self) {
-        self.total = match self.phase {
+    /// 恢复保存的状态；`resume` 为假时只保留每日计数与连续天数
+    fn restore(&mut self, saved: &state::SavedState, resume: bool) {
+        let today = self.clock.local().date();
+        self.day = saved.day;
+        self.day_completed = saved.day_completed;
+        self.day_interruptions = saved.day_interruptions;
+        self.streak = saved.streak;
+        self.debt_reset = saved.debt_reset;
+        // `--start-phase` 指定的阶段优先于保存的阶段，完成数照常恢复
+        if resume && self.config.start_phase.is_none() {
+            self.phase = saved.phase;
+            // 恢复到休息阶段时不需要热身
+            if self.phase != Phase::Focus && self.warming_up {
+                self.ready_until = None;
+                self.warming_up = false;
+            }
+            self.total = Duration::from_secs(saved.total_secs);
+            self.remaining = saved.remaining();
+            self.logged = Duration::from_secs(saved.logged_secs);
+            // 保存时只有剩余时间，按它估算已经计过的时长
+            self.ran = self.total.saturating_sub(self.remaining);
+        }
+        if resume {
+            // 长休息节奏按天重新计算
+            if saved.day == today {
+                self.completed_focus = saved.completed_focus;
+                self.run_baseline = saved.completed_focus;
+            }
+            self.pick_message();
+        }
+        // 命令行预设的数量优先于保存的数量
+        if let Some(completed) = self.config.completed {
+            self.completed_focus = completed;
+            self.run_baseline = completed;
+        }
+        self.roll_day(today);
+    }
+
+    fn snapshot(&self) -> state::SavedState {
+        state::SavedState {
+            phase: self.phase,
+            total_secs: self.total.as_secs(),
+            remaining_secs: self.remaining.as_secs(),
+            remaining_ms: Some(self.remaining.as_millis() as u64),
+            completed_focus: self.completed_focus,
+            day: self.day,
+            day_completed: self.day_completed,
+            streak: self.streak,
+            debt_reset: self.debt_reset,
+            day_interruptions: self.day_interruptions,
+            logged_secs: self.logged.as_secs(),
+        }
+    }
+
+    /// 表头显示的完成数，按 `--count-scope`
+    fn completed(&self) -> u32 {
+        match self.config.count_scope {
+            config::CountScope::Run => self.completed_focus,
+            config::CountScope::Day => self.day_completed,
+            config::CountScope::Week => self.week_completed,
+        }
+    }
+
+    fn live_status(&self) -> status::LiveStatus {
+        status::LiveStatus {
+            phase: self.phase,
+            total_secs: self.total.as_secs(),
+            remaining_secs: self.remaining.as_secs(),
+            running: self.running,
+            completed_focus: self.completed_focus,
+            day_completed: self.day_completed,
+            updated_at: Utc::now().timestamp_millis(),
+        }
+    }
+
+    /// 跨天时重置每日计数；前一天没有完成专注则连续天数归零
+    fn roll_day(&mut self, today: NaiveDate) {
+        if today == self.day {
+            return;
+        }
+        let continued = self.day_completed > 0 && self.day.succ_opt() == Some(today);
+        if !continued {
+            self.streak = 0;
+        }
+        if stats::week_start(today) != stats::week_start(self.day) {
+            self.week_completed = 0;
+        }
+        self.day = today;
+        self.day_completed = 0;
+        self.day_interruptions = 0;
+    }
+
+    /// 每个专注阶段开始时选一条激励语
+    fn pick_message(&mut self) {
+        let count = self.config.messages.len();
+        if self.phase != Phase::Focus || count == 0 {
+            self.message = None;
+            return;
+        }
+        let index = match self.config.message_order {
+            config::MessageOrder::Sequential => {
+                let index = self.next_message % count;
+                self.next_message = index + 1;
+                index
+            }
+            config::MessageOrder::Random => {
+                let nanos = SystemTime::now()
+                    .duration_since(UNIX_EPOCH)
+                    .map(|d| d.subsec_nanos() as usize)
+                    .unwrap_or(0);
+                nanos % count
+            }
+        };
+        self.message = Some(index);
+    }
+
+    fn current_message(&self) -> Option<&str> {
+        self.message
+            .and_then(|index| self.config.messages.get(index))
+            .map(String::as_str)
+    }
+
+    /// 当前阶段按配置应有的时长
+    fn configured_length(&self) -> Duration {
+        match self.phase {
             Phase::Focus => self.config.focus,
             Phase::ShortBreak => self.config.short_break,
             Phase::LongBreak => self.config.long_break,
-        };
+        }
+    }
+
+    fn reset_current(&mut self) {
+        // 正常进入或重置的阶段从头开始，之前切走时保存的进度作废
+        self.stashed.remove(&self.phase);
+        self.total = self.configured_length();
         self.remaining = self.total;
+        self.phase_started = None;
+        self.phase_interruptions = 0;
+        self.phase_note = None;
+        self.overtime = None;
+        self.logged = Duration::ZERO;
+        self.ran = Duration::ZERO;
     }
 
     fn toggle(&mut self) {
-        self.running = !self.running;
+        if self.battery_alert.take().is_some() {
+            // 低电量暂停需手动恢复
+            self.running = true;
+        } else if self.auto_paused || self.idle_paused {
+            // 自动暂停中按下视为手动暂停
+            self.auto_paused = false;
+            self.idle_paused = false;
+        } else {
+            if self.running {
+                self.count_interruption();
+            }
+            self.running = !self.running;
+        }
     }
 
-    fn skip(&mut self) {
-        self.to_next_phase();
+    /// 单击：关闭弹窗、跳过就绪倒计时，否则切换开始/暂停
+    fn click(&mut self) {
+        if self.prompt.is_some() || self.close_popup() {
+            return;
+        }
+        if self.alerting.is_some() {
+            self.acknowledge();
+        } else if self.ready_until.is_some() {
+            self.start_ready();
+        } else {
+            self.toggle();
+        }
     }
 
-    fn update(&mut self) {
-        if !self.running {
-            self.last_tick = Instant::now();
+    /// 关闭打开的弹窗，返回是否有弹窗被关闭
+    fn close_popup(&mut self) -> bool {
+        self.help.take().is_some()
+            || self.battery_alert.take().is_some()
+            || self.splash.take().is_some()
+    }
+
+    fn show_splash(&mut self, summary: stats::DaySummary) {
+        self.splash = Some((summary, self.clock.now() + SPLASH_DURATION));
+    }
+
+    fn set_battery(&mut self, level: u8) {
+        let Some(threshold) = self.config.pause_on_low_battery else {
             return;
+        };
+        if level >= threshold {
+            self.battery_tripped = false;
+        } else if !self.battery_tripped {
+            self.battery_tripped = true;
+            self.running = false;
+            self.battery_alert = Some(level);
         }
-        let now = Instant::now();
-        let delta = now.saturating_duration_since(self.last_tick);
-        self.last_tick = now;
+    }
 
-        if delta >= self.remaining {
-            self.remaining = Duration::ZERO;
-            self.on_finish();
-        } else {
-            self.remaining -= delta;
+    /// 默认音频输出变了（多半是拔了耳机、有人来打断），暂停进行中的专注，按 Space 继续
+    fn audio_changed(&mut self) {
+        if self.config.pause_on_audio_change && self.running && self.phase == Phase::Focus {
+            self.count_interruption();
+            self.running = false;
+            self.notice("Paused: the audio output changed".to_string());
         }
     }
 
-    fn on_finish(&mut self) {
-        if !self.config.mute {
-            // 终端响铃
-            print!("\x07");
-            let _ = io::Write::flush(&mut io::stdout());
+    fn set_foreground(&mut self, focused: bool) {
+        self.foreground_ok = focused;
+        if focused && self.auto_paused {
+            self.auto_paused = false;
+            self.running = true;
         }
+    }
 
-        match self.phase {
-            Phase::Focus => {
-                self.completed_focus += 1;
-                let use_long = self.completed_focus % self.config.long_every == 0;
-                self.phase = if use_long {
-                    Phase::LongBreak
-                } else {
-                    Phase::ShortBreak
-                };
-            }
-            Phase::ShortBreak | Phase::LongBreak => {
-                self.phase = Phase::Focus;
-            }
+    fn set_idle(&mut self, idle: bool) {
+        self.user_idle = idle;
+        if !idle && self.idle_paused {
+            self.idle_paused = false;
+            self.running = true;
         }
-        self.reset_current();
-        self.running = true; // 自动开始下一阶段
     }
 
-    fn to_next_phase(&mut self) {
-        match self.phase {
-            Phase::Focus => {
-                self.phase = Phase::ShortBreak;
-            }
-            Phase::ShortBreak => {
-                self.phase = Phase::Focus;
-            }
-            Phase::LongBreak => {
-                self.phase = Phase::Focus;
-            }
-        }
-        self.reset_current();
+    /// 是否已完成 `--max-sessions` 计划或到了 `--until` 的时间
+    fn plan_complete(&self) -> bool {
+        self.workday_over
+            || self
+                .config
+                .max_sessions
+                .is_some_and(|max| self.completed_focus - self.run_baseline >= max)
     }
 
-    fn formatted_remaining(&self) -> String {
-        let total_secs = self.remaining.as_secs();
-        let minutes = total_secs / 60;
-        let seconds = total_secs % 60;
-        format!("{:02}:{:02}", minutes, seconds)
+    /// 计划完成后等待按键退出
+    fn showing_done(&self) -> bool {
+        self.config.done_screen && self.plan_complete()
     }
 
-    fn progress_ratio(&self) -> f64 {
-        if self.total.is_zero() {
-            return 0.0;
+    fn past_until(&self) -> bool {
+        self.config
+            .until
+            .is_some_and(|until| self.clock.local() >= until)
+    }
+
+    fn touch(&mut self) {
+        self.last_activity = self.clock.now();
+    }
+
+    /// 切换动画的进度（0.0..1.0），动画结束后为 `None`
+    fn transition_progress(&self) -> Option<f64> {
+        let elapsed = self.clock.now().saturating_duration_since(self.transition?);
+        (elapsed < TRANSITION_DURATION)
+            .then(|| elapsed.as_secs_f64() / TRANSITION_DURATION.as_secs_f64())
+    }
+
+    fn start_flash(&mut self) {
+        if self.config.alerts.contains(&config::AlertBackend::Flash) {
+            self.flash = Some(self.clock.now());
         }
-        let elapsed = self.total.saturating_sub(self.remaining);
-        elapsed.as_secs_f64() / self.total.as_secs_f64()
     }
-}
 
-fn ui(frame: &mut ratatui::Frame, app: &PomodoroApp) {
-    let size = frame.size();
+    /// 视觉铃进行中时返回这一帧是否反色，结束后为 `None`
+    fn flash_inverted(&self) -> Option<bool> {
+        let elapsed = self.clock.now().saturating_duration_since(self.flash?);
+        let step = elapsed.as_millis() / FLASH_STEP.as_millis();
+        (step < u128::from(FLASH_COUNT) * 2).then_some(step.is_multiple_of(2))
+    }
 
-    let layout = Layout::default()
-        .direction(Direction::Vertical)
-        .margin(1)
-        .constraints(
-            [
-                Constraint::Length(5), // header
-                Constraint::Length(4), // gauge
-                Constraint::Min(7),    // big timer
-                Constraint::Length(3), // help
-            ]
-            .as_ref(),
-        )
-        .split(size);
+    /// 暂停提示的呼吸效果：从上次操作起每 `PAUSE_PULSE` 切换一次，刚按下时总是正常亮度
+    fn pause_dimmed(&self) -> bool {
+        if !self.config.animations {
+            return false;
+        }
+        let elapsed = self
+            .clock
+            .now()
+            .saturating_duration_since(self.last_activity);
+        (elapsed.as_millis() / PAUSE_PULSE.as_millis()) % 2 == 1
+    }
 
-    // Header
-    let accent = app.phase.color();
-    let title = Line::from(vec![
-        Span::styled("● ", Style::default().fg(accent)),
-        Span::styled(
-            app.phase.name(),
-            Style::default().fg(accent).add_modifier(Modifier::BOLD),
+    fn idle_expired(&self) -> bool {
+        let idle = self.config.auto_quit_idle;
+        !idle.is_zero()
+            && self
+                .clock
+                .now()
+                .saturating_duration_since(self.last_activity)
+                >= idle
+    }
+
+    /// 退出时把进行中的阶段按已计时的部分记为未完成；刚好在同一帧结束的阶段已由
+    /// `complete_phase` 记录，此时下一阶段还没开始计时，不会重复记录
+    fn record_partial(&mut self) {
+        let Some(start) = self.phase_started else {
+            return;
+        };
+        let secs = self.ran.saturating_sub(self.logged).as_secs();
+        if secs == 0 {
+            return;
+        }
+        self.logged += Duration::from_secs(secs);
+        self.pending_records.push(history::SessionRecord {
+            start,
+            end: self.clock.local(),
+            phase: self.phase,
+            duration_secs: history::round_secs(secs, self.config.round_log),
+            batch: self.batch.as_ref().map(|b| b.label.clone()),
+            interruptions: self.phase_interruptions,
+            note: self.phase_note.clone(),
+            completed: false,
+        });
+    }
+
+    fn quit_outcome(&self) -> Outcome {
+        if (self.config.max_sessions.is_some() || self.config.until.is_some())
+            && !self.plan_complete()
+        {
+            Outcome::QuitEarly
+        } else {
+            Outcome::Finished
+        }
+    }
+
+    fn perform(&mut self, action: actions::Action) {
+        match action {
+            actions::Action::Toggle => self.toggle(),
+            actions::Action::Skip => self.skip(),
+            actions::Action::SwitchTo(phase) => self.switch_to(phase),
+            actions::Action::Reset => self.reset_current(),
+            actions::Action::GoTo => self.open_prompt(PromptKind::GoTo),
+            actions::Action::BatchLabel => self.open_prompt(PromptKind::BatchLabel),
+            actions::Action::EndBatch => self.end_batch(),
+            actions::Action::NewBlock => self.new_block(),
+            actions::Action::Note => self.open_prompt(PromptKind::Note),
+            actions::Action::LogSession => self.open_prompt(PromptKind::LogSession),
+            actions::Action::Interruption => self.note_interruption(),
+            actions::Action::Help => self.help = Some(0),
+            actions::Action::EditConfig => self.edit_requested = true,
+            actions::Action::SwitchPreset => self.switch_preset(false),
+            actions::Action::SwitchPresetNow => self.switch_preset(true),
+            actions::Action::ToggleMute => self.toggle_mute(),
+            actions::Action::ToggleTime => {
+                self.config.hide_time = !self.config.hide_time;
+                self.notice(
+                    if self.config.hide_time {
+                        "Focus time hidden"
+                    } else {
+                        "Focus time shown"
+                    }
+                    .to_string(),
+                );
+            }
+            actions::Action::ResetCount => self.open_prompt(PromptKind::ResetCount),
+            actions::Action::NextProfile => self.next_profile(),
+        }
+    }
+
+    /// 主循环里没被弹窗等拦下的按键：组合键的第一个键先记下，等第二个键或超时
+    fn press(&mut self, code: KeyCode) {
+        if let Some((first, _)) = self.pending_chord.take() {
+            if let Some(action) = actions::lookup_chord(first, code) {
+                self.perform(action);
+                return;
+            }
+            // 不成组合：第一个键照单键处理，它打开了输入框时这个键就是输入的内容
+            self.press_single(first);
+            if self.prompt.is_some() {
+                self.prompt_input(code);
+                return;
+            }
+        }
+        if actions::starts_chord(code) {
+            self.pending_chord = Some((code, self.clock.now()));
+        } else {
+            self.press_single(code);
+        }
+    }
+
+    fn press_single(&mut self, code: KeyCode) {
+        if let Some(action) = actions::lookup(code) {
+            self.perform(action);
+        }
+    }
+
+    /// 清零累计完成数，长休息节奏和 `--max-sessions` 的进度也从头算；今天的计数不变
+    fn reset_count(&mut self) {
+        let was = self.completed_focus;
+        self.completed_focus = 0;
+        self.run_baseline = 0;
+        self.notice(format!("Completed count reset (was {was})"));
+    }
+
+    fn toggle_mute(&mut self) {
+        self.config.mute = !self.config.mute;
+        // 响铃由观察者负责，按新的设置重新注册
+        self.observers.clear();
+        add_default_observers(self);
+        self.notice(if self.config.mute { "Muted" } else { "Unmuted" }.to_string());
+    }
+
+    /// 当前生效的提醒方式：响铃、提示音、实时通知和视觉铃
+    fn alert_channels(&self) -> Vec<&'static str> {
+        let ascii = self.config.icons.ascii;
+        let alerts = &self.config.alerts;
+        let has = |backend| alerts.contains(&backend);
+        let mut channels = Vec::new();
+        if self.config.mute && (has(config::AlertBackend::Bell) || has(config::AlertBackend::Sound))
+        {
+            channels.push(if ascii { "muted" } else { "🔕" });
+        } else {
+            let audible = has(config::AlertBackend::Sound) && self.config.cue().audible();
+            if audible {
+                channels.push(if ascii { "sound" } else { "🔊" });
+            }
+            if has(config::AlertBackend::Bell) || (has(config::AlertBackend::Sound) && !audible) {
+                channels.push(if ascii { "bell" } else { "🔔" });
+            }
+        }
+        if has(config::AlertBackend::Notify) || self.config.live_notify {
+            channels.push(if ascii { "notify" } else { "📢" });
+        }
+        if has(config::AlertBackend::Flash) {
+            channels.push(if ascii { "flash" } else { "⚡" });
+        }
+        channels
+    }
+
+    fn current_durations(&self) -> Preset {
+        Preset {
+            focus: self.config.focus,
+            short_break: self.config.short_break,
+            long_break: self.config.long_break,
+        }
+    }
+
+    fn matching_preset(&self) -> Option<usize> {
+        let current = self.current_durations();
+        self.config.presets?.iter().position(|p| *p == current)
+    }
+
+    /// 当前时长对应的 `[profiles]` 项；几项时长相同时取当前这项或第一项
+    fn matching_profile(&self) -> Option<usize> {
+        let current = self.current_durations();
+        let profiles = &self.config.profiles;
+        self.profile
+            .filter(|&i| profiles.get(i).is_some_and(|(_, p)| *p == current))
+            .or_else(|| profiles.iter().position(|(_, p)| *p == current))
+    }
+
+    /// 换到下一个 `[profiles]` 项：当前阶段按新时长从头开始（已计时的部分记为未完成），
+    /// 完成数和长休息节奏不变
+    fn next_profile(&mut self) {
+        if self.config.profiles.is_empty() {
+            self.notice(
+                "No profiles: add [profiles.<name>] sections to the config file".to_string(),
+            );
+            return;
+        }
+        let next = self
+            .profile
+            .map_or(0, |i| (i + 1) % self.config.profiles.len());
+        let (name, profile) = self.config.profiles[next].clone();
+        self.config.focus = profile.focus;
+        self.config.short_break = profile.short_break;
+        self.config.long_break = profile.long_break;
+        self.profile = Some(next);
+        self.preset = self.matching_preset();
+        self.record_partial();
+        self.reset_current();
+        let minutes = |d: Duration| d.as_secs() / 60;
+        self.notice(format!(
+            "Profile {name}: {}/{}/{} min",
+            minutes(profile.focus),
+            minutes(profile.short_break),
+            minutes(profile.long_break)
+        ));
+    }
+
+    /// 换用另一组时长。之后的阶段都按新时长；还没开始计时的阶段、或 `now` 为真时，
+    /// 当前阶段也换成新时长，已经过去的时间保留
+    fn switch_preset(&mut self, now: bool) {
+        let Some(presets) = self.config.presets else {
+            self.notice("No presets: add [preset.a] and [preset.b] to the config file".to_string());
+            return;
+        };
+        let next = match self.preset {
+            Some(0) => 1,
+            _ => 0,
+        };
+        let preset = presets[next];
+        self.config.focus = preset.focus;
+        self.config.short_break = preset.short_break;
+        self.config.long_break = preset.long_break;
+        self.preset = Some(next);
+        self.profile = self.matching_profile();
+        let resized = now || self.phase_started.is_none();
+        if resized && self.overtime.is_none() {
+            let elapsed = self.total.saturating_sub(self.remaining);
+            self.total = self.configured_length();
+            self.remaining = self.total.saturating_sub(elapsed);
+        }
+        let minutes = |d: Duration| d.as_secs() / 60;
+        self.notice(format!(
+            "Preset {}: {}/{}/{} min{}",
+            preset_name(next),
+            minutes(preset.focus),
+            minutes(preset.short_break),
+            minutes(preset.long_break),
+            if resized { "" } else { " from the next phase" }
+        ));
+    }
+
+    /// 应用重新加载的配置：当前阶段的进度不变，新的时长从下一阶段开始生效
+    fn reload_config(&mut self, mut config: PomodoroConfig) {
+        // 监视线程、鼠标捕获和屏幕缓冲区在启动时就已确定，沿用原来的设置
+        config.pause_unless_process = self.config.pause_unless_process.take();
+        config.pause_on_low_battery = self.config.pause_on_low_battery;
+        config.pause_on_audio_change = self.config.pause_on_audio_change;
+        config.auto_pause_idle = self.config.auto_pause_idle;
+        config.mouse = self.config.mouse;
+        config.alternate_screen = self.config.alternate_screen;
+        config.live_notify = self.config.live_notify;
+        // 截止时刻按启动时的日期换算，重新读取会把已过的时间算到明天
+        config.until = self.config.until;
+        self.config = config;
+        self.preset = self.matching_preset();
+        self.profile = self.matching_profile();
+        self.notice("Config reloaded".to_string());
+    }
+
+    /// 帮助面板打开时的按键：滚动或关闭，其余忽略
+    fn help_input(&mut self, code: KeyCode) {
+        let Some(scroll) = self.help else {
+            return;
+        };
+        let scroll = match code {
+            KeyCode::Esc | KeyCode::Char('?') => {
+                self.help = None;
+                return;
+            }
+            KeyCode::Up | KeyCode::Char('k') => scroll.saturating_sub(1),
+            KeyCode::Down | KeyCode::Char('j') => scroll.saturating_add(1),
+            KeyCode::PageUp => scroll.saturating_sub(HELP_PAGE),
+            KeyCode::PageDown => scroll.saturating_add(HELP_PAGE),
+            KeyCode::Home => 0,
+            _ => scroll,
+        };
+        self.help = Some(scroll.min(help_lines(self).len() as u16));
+    }
+
+    fn open_prompt(&mut self, kind: PromptKind) {
+        // 备注可再次编辑
+        let input = match kind {
+            PromptKind::Note => self.phase_note.clone().unwrap_or_default(),
+            _ => String::new(),
+        };
+        self.prompt = Some(Prompt {
+            kind,
+            input,
+            error: None,
+        });
+    }
+
+    fn prompt_input(&mut self, code: KeyCode) {
+        let Some(prompt) = self.prompt.as_mut() else {
+            return;
+        };
+        if prompt.kind == PromptKind::ResetCount {
+            self.prompt = None;
+            if matches!(code, KeyCode::Char('y' | 'Y')) {
+                self.reset_count();
+            }
+            return;
+        }
+        match code {
+            KeyCode::Esc => self.prompt = None,
+            KeyCode::Enter => self.submit_prompt(),
+            KeyCode::Backspace => {
+                prompt.input.pop();
+                prompt.error = None;
+            }
+            KeyCode::Char(c) => {
+                prompt.input.push(c);
+                prompt.error = None;
+            }
+            _ => {}
+        }
+    }
+
+    /// 粘贴的文本只在输入框打开时作为输入，其余时候忽略
+    fn paste(&mut self, text: &str) {
+        if let Some(prompt) = self.prompt.as_mut() {
+            // 换行等空白折叠为空格，其余控制字符丢弃
+            let text = text
+                .chars()
+                .map(|c| if c.is_whitespace() { ' ' } else { c })
+                .filter(|c| !c.is_control());
+            prompt.input.extend(text);
+            prompt.error = None;
+        }
+    }
+
+    fn submit_prompt(&mut self) {
+        let Some(prompt) = self.prompt.as_mut() else {
+            return;
+        };
+        match prompt.kind {
+            PromptKind::GoTo => match parse_clock(prompt.input.trim()) {
+                Some(remaining) => {
+                    self.prompt = None;
+                    self.go_to(remaining);
+                }
+                None => prompt.error = Some("expected MM:SS"),
+            },
+            PromptKind::Note => {
+                let note = prompt.input.trim().to_string();
+                self.prompt = None;
+                self.phase_note = (!note.is_empty()).then_some(note);
+            }
+            PromptKind::LogSession => {
+                let text = prompt.input.trim();
+                let length = if text.is_empty() {
+                    Ok(self.config.focus)
+                } else {
+                    parse_duration(text)
+                };
+                match length {
+                    Ok(length) if !length.is_zero() => {
+                        self.prompt = None;
+                        self.log_session(length);
+                    }
+                    _ => prompt.error = Some("expected a duration like 25m or 1h30m"),
+                }
+            }
+            PromptKind::ResetCount => {}
+            PromptKind::BatchLabel => {
+                let label = prompt.input.trim().to_string();
+                if label.is_empty() {
+                    prompt.error = Some("label can't be empty");
+                } else {
+                    self.prompt = None;
+                    self.start_batch(label);
+                }
+            }
+        }
+    }
+
+    /// 把在别处完成的专注记为刚刚结束的一条完整记录，计数照常增加；当前阶段和计时不受影响
+    fn log_session(&mut self, length: Duration) {
+        let end = self.clock.local();
+        let start = TimeDelta::from_std(length)
+            .ok()
+            .and_then(|delta| end.checked_sub_signed(delta))
+            .unwrap_or(end);
+        let record = history::SessionRecord {
+            start,
+            end,
+            phase: Phase::Focus,
+            duration_secs: history::round_secs(length.as_secs(), self.config.round_log),
+            batch: None,
+            interruptions: 0,
+            note: None,
+            completed: true,
+        };
+        for observer in &mut self.observers {
+            observer.on_phase_complete(&record);
+        }
+        self.pending_records.push(record);
+        self.focused_total += length;
+        self.completed_focus += 1;
+        self.roll_day(end.date());
+        self.day_completed += 1;
+        self.week_completed += 1;
+        if self.day_completed == 1 {
+            self.streak += 1;
+        }
+        self.notice(format!(
+            "Logged a {} focus session (today: {})",
+            format_duration(length),
+            self.day_completed
+        ));
+    }
+
+    fn start_batch(&mut self, label: String) {
+        self.end_batch();
+        self.notice(format!("Batch \"{label}\" started"));
+        self.batch = Some(Batch {
+            label,
+            started: self.clock.local(),
+            sessions: 0,
+            focus: Duration::ZERO,
+        });
+    }
+
+    /// 结束当前批次：写入批次记录并在页脚展示汇总
+    fn end_batch(&mut self) {
+        let Some(batch) = self.batch.take() else {
+            return;
+        };
+        self.notice(format!(
+            "Batch \"{}\" done: {} sessions, {} focus",
+            batch.label,
+            batch.sessions,
+            timeline::format_secs(batch.focus.as_secs())
+        ));
+        self.pending_batches.push(history::BatchRecord {
+            start: batch.started,
+            end: self.clock.local(),
+            label: batch.label,
+            sessions: batch.sessions,
+            focus_secs: batch.focus.as_secs(),
+        });
+    }
+
+    /// 已完成的工作块，完成汇总显示中
+    fn finished_block(&self) -> Option<&WorkBlock> {
+        self.work_block
+            .as_ref()
+            .filter(|block| block.finished.is_some())
+    }
+
+    /// 开始新的工作块：进行中的阶段记为未完成，从一个未开始的专注重新计数
+    fn new_block(&mut self) {
+        let Some(size) = self.config.block else {
+            self.notice("Work blocks need --block N".to_string());
+            return;
+        };
+        self.record_partial();
+        self.phase = Phase::Focus;
+        self.reset_current();
+        self.running = false;
+        self.ready_until = None;
+        self.work_block = Some(WorkBlock::new(self.clock.local()));
+        self.notice(format!(
+            "New block of {}",
+            self.config.numbers.plural(size, "session")
+        ));
+    }
+
+    /// 收到 `--duration-source` 的一行；无法解析时这一次专注用默认时长
+    fn queue_focus(&mut self, line: durations::Line) {
+        match line {
+            Ok(focus) => self.queued_focus.push_back(focus),
+            Err(err) => {
+                // 界面占着终端，stderr 被重定向时才写日志
+                if !io::stderr().is_terminal() {
+                    eprintln!("warning: --duration-source: {err}");
+                }
+                self.notice(format!("Ignored duration: {err}"));
+                self.queued_focus.push_back(None);
+            }
+        }
+    }
+
+    /// 在页脚短暂显示一条提示
+    fn notice(&mut self, text: String) {
+        self.notice = Some((text, self.clock.now()));
+    }
+
+    fn current_notice(&self) -> Option<&str> {
+        self.notice
+            .as_ref()
+            .filter(|(_, at)| self.clock.now().saturating_duration_since(*at) < NOTICE_DURATION)
+            .map(|(text, _)| text.as_str())
+    }
+
+    /// 直接设置剩余时间；超过本阶段总时长时同步延长总时长
+    fn go_to(&mut self, remaining: Duration) {
+        if remaining > self.total {
+            self.total = remaining;
+        }
+        self.remaining = remaining;
+        self.overtime = None;
+    }
+
+    /// 手动跳到下一阶段：不写历史、不计入完成数，也不改变是否在计时
+    /// （到点完成则由 `complete_phase` 记录并自动开始下一阶段）
+    fn skip(&mut self) {
+        if let Some(left) = self.break_lock_remaining() {
+            let secs = left.as_secs() + u64::from(left.subsec_nanos() > 0);
+            self.notice(format!("Rest a little longer (skip in {secs}s)"));
+            return;
+        }
+        // 超时中按 n 结束本次专注，连同超时一起记录
+        if self.overtime.is_some() {
+            self.complete_phase();
+            return;
+        }
+        // `--confirm-skip`：专注要在时限内再按一次才跳过，防止误触
+        if self.phase == Phase::Focus && !self.config.confirm_skip.is_zero() {
+            let now = self.clock.now();
+            let confirmed = self.last_skip_press.take().is_some_and(|first| {
+                now.saturating_duration_since(first) <= self.config.confirm_skip
+            });
+            if !confirmed {
+                self.last_skip_press = Some(now);
+                self.notice("Press n again to skip".to_string());
+                return;
+            }
+            self.notice = None;
+        }
+        if self.running || self.phase_started.is_some() {
+            self.count_interruption();
+        }
+        if self.phase == Phase::Focus {
+            self.skipped_focus += 1;
+        }
+        self.advance_phase();
+    }
+
+    /// 按 1/2/3 直接切换到某个阶段，不写入历史、保持原来的运行或暂停状态。
+    /// `--resume-remaining` 下切走的阶段留着剩余时间，切回来时接着计时
+    fn switch_to(&mut self, phase: Phase) {
+        if phase == self.phase {
+            return;
+        }
+        if let Some(left) = self.break_lock_remaining() {
+            let secs = left.as_secs() + u64::from(left.subsec_nanos() > 0);
+            self.notice(format!("Rest a little longer (switch in {secs}s)"));
+            return;
+        }
+        // 超时中的专注先按完成记录
+        if self.overtime.is_some() {
+            self.complete_phase();
+            if phase == self.phase {
+                return;
+            }
+        }
+        if self.running || self.phase_started.is_some() {
+            self.count_interruption();
+        }
+        match self.phase_started {
+            Some(started) if self.config.resume_remaining => {
+                let stashed = Stashed {
+                    total: self.total,
+                    remaining: self.remaining,
+                    started,
+                    interruptions: self.phase_interruptions,
+                    note: self.phase_note.take(),
+                    logged: self.logged,
+                    ran: self.ran,
+                };
+                self.stashed.insert(self.phase, stashed);
+            }
+            _ if self.phase == Phase::Focus => self.skipped_focus += 1,
+            _ => {}
+        }
+        let stashed = self.stashed.remove(&phase);
+        self.phase = phase;
+        self.reset_current();
+        if let Some(stashed) = stashed {
+            self.total = stashed.total;
+            self.remaining = stashed.remaining;
+            self.phase_started = Some(stashed.started);
+            self.phase_interruptions = stashed.interruptions;
+            self.phase_note = stashed.note;
+            self.logged = stashed.logged;
+            self.ran = stashed.ran;
+        }
+        self.pick_message();
+    }
+
+    /// 已开始的专注被暂停、跳过，或按 `i` 记下一次打断
+    fn count_interruption(&mut self) {
+        if self.phase == Phase::Focus {
+            self.roll_day(self.clock.local().date());
+            self.day_interruptions += 1;
+            self.phase_interruptions += 1;
+            if let Some(block) = self.work_block.as_mut().filter(|b| b.finished.is_none()) {
+                block.interruptions += 1;
+            }
+        }
+    }
+
+    /// 按 `i` 手动记一次打断（番茄工作法的“内部/外部打断”），不影响计时；只在专注中计数
+    fn note_interruption(&mut self) {
+        if self.phase != Phase::Focus {
+            self.notice("Interruptions are counted during focus".to_string());
+            return;
+        }
+        self.count_interruption();
+        self.notice(format!(
+            "Interruption noted ({} this session)",
+            self.phase_interruptions
+        ));
+    }
+
+    /// 休息中距离允许跳过还剩多久（`--min-break` 不超过本次休息的总时长）
+    fn break_lock_remaining(&self) -> Option<Duration> {
+        if self.phase == Phase::Focus {
+            return None;
+        }
+        let min = self.config.min_break.min(self.total);
+        let elapsed = self.total.saturating_sub(self.remaining);
+        let left = min.saturating_sub(elapsed);
+        (!left.is_zero()).then_some(left)
+    }
+
+    fn start_ready(&mut self) {
+        self.ready_until = None;
+        self.warming_up = false;
+        self.running = true;
+        self.last_tick = self.clock.now();
+    }
+
+    fn ready_remaining(&self) -> Option<Duration> {
+        self.ready_until
+            .map(|until| until.saturating_duration_since(self.clock.now()))
+    }
+
+    fn update(&mut self) {
+        if let Some((first, at)) = self.pending_chord
+            && self.clock.now().saturating_duration_since(at) >= actions::CHORD_TIMEOUT
+        {
+            self.pending_chord = None;
+            self.press_single(first);
+        }
+        // 还没开始计时的阶段不必等它结束
+        if self.phase_started.is_none() && self.past_until() {
+            self.workday_over = true;
+        }
+        if self
+            .splash
+            .as_ref()
+            .is_some_and(|(_, until)| self.clock.now() >= *until)
+        {
+            self.splash = None;
+        }
+        if let Some(alerting) = &mut self.alerting {
+            let now = self.clock.now();
+            if now.saturating_duration_since(alerting.last_ring) >= ALERT_REPEAT {
+                alerting.last_ring = now;
+                if !self.config.mute {
+                    alert::sound(&self.config.alerts, &self.config.cue());
+                }
+                self.start_flash();
+            }
+        }
+        if let Some(until) = self.ready_until
+            && self.clock.now() >= until
+        {
+            self.start_ready();
+        }
+        if !self.foreground_ok && self.running && self.phase == Phase::Focus {
+            self.running = false;
+            self.auto_paused = true;
+        }
+        if self.user_idle && self.running && self.phase == Phase::Focus {
+            self.running = false;
+            self.idle_paused = true;
+        }
+        if self.running != self.observed_running {
+            self.observed_running = self.running;
+            let (phase, remaining) = (self.phase, self.remaining);
+            for observer in &mut self.observers {
+                if self.running {
+                    observer.on_resume(phase, remaining);
+                } else {
+                    observer.on_pause(phase, remaining);
+                }
+            }
+        }
+        if !self.running {
+            if self.remaining.is_zero()
+                && !self.total.is_zero()
+                && self.ready_until.is_none()
+                && self.overtime.is_none()
+                && self.config.zero_behavior == config::ZeroBehavior::Finish
+            {
+                self.on_finish();
+                self.running = false;
+                self.ready_until = None;
+                if let Some(alerting) = &mut self.alerting {
+                    alerting.start = false;
+                }
+            }
+            self.last_tick = self.clock.now();
+            return;
+        }
+        if self.phase_started.is_none() {
+            // 专注开始计时时取下一个外部时长；已经用 g 调整过的不覆盖
+            if self.phase == Phase::Focus
+                && self.remaining == self.total
+                && let Some(Some(focus)) = self.queued_focus.pop_front()
+            {
+                self.total = focus;
+                self.remaining = focus;
+            }
+            self.phase_started = Some(self.clock.local());
+            let (phase, total) = (self.phase, self.total);
+            for observer in &mut self.observers {
+                observer.on_phase_start(phase, total);
+            }
+        }
+        let now = self.clock.now();
+        let delta = now.saturating_duration_since(self.last_tick);
+        self.last_tick = now;
+        match self.phase {
+            Phase::Focus => self.focused_total += delta,
+            Phase::ShortBreak | Phase::LongBreak => self.break_total += delta,
+        }
+
+        self.ran += match self.overtime {
+            Some(_) => delta,
+            None => delta.min(self.remaining),
+        };
+        if let Some(overtime) = &mut self.overtime {
+            *overtime += delta;
+        } else if delta >= self.remaining {
+            self.remaining = Duration::ZERO;
+            self.on_finish();
+            return;
+        } else {
+            self.remaining -= delta;
+        }
+        let (phase, remaining) = (self.phase, self.remaining);
+        for observer in &mut self.observers {
+            observer.on_tick(phase, remaining);
+        }
+    }
+
+    /// 注册事件观察者，按注册顺序调用
+    fn observe(&mut self, observer: Box<dyn observer::PomodoroObserver>) {
+        self.observers.push(observer);
+    }
+
+    fn on_finish(&mut self) {
+        let phase = self.phase;
+        for observer in &mut self.observers {
+            observer.on_time_up(phase);
+        }
+        self.start_flash();
+        self.touch();
+        if self.config.overrun && self.phase == Phase::Focus {
+            // 不自动切换，继续计时到手动结束
+            self.overtime = Some(Duration::ZERO);
+            return;
+        }
+        self.complete_phase();
+        if self.config.alert_until_ack {
+            // 停在下一阶段开头，确认后再按原计划开始
+            self.alerting = Some(Alerting {
+                last_ring: self.clock.now(),
+                start: self.running || self.ready_until.is_some(),
+            });
+            self.running = false;
+            self.ready_until = None;
+        }
+    }
+
+    /// 确认到点提醒；`--alert-until-ack` 下任意按键或单击都会先走到这里
+    fn acknowledge(&mut self) {
+        if let Some(alerting) = self.alerting.take()
+            && alerting.start
+        {
+            self.auto_start();
+        }
+    }
+
+    /// 自动开始当前阶段：专注前先展示 "Get ready" 倒计时
+    fn auto_start(&mut self) {
+        self.ready_until = if self.phase == Phase::Focus && !self.config.ready_countdown.is_zero() {
+            self.clock.now().checked_add(self.config.ready_countdown)
+        } else {
+            None
+        };
+        self.running = self.ready_until.is_none();
+        if self.running {
+            self.last_tick = self.clock.now();
+        }
+    }
+
+    /// 记录当前阶段并切换到下一阶段
+    fn complete_phase(&mut self) {
+        if self.config.animations {
+            self.transition = Some(self.clock.now());
+        }
+
+        let end = self.clock.local();
+        // 只记实际计过的时间；上次退出时已记下的部分不再计入
+        let counted = self.ran.saturating_sub(self.logged);
+        let record = history::SessionRecord {
+            start: self.phase_started.unwrap_or(end),
+            end,
+            phase: self.phase,
+            duration_secs: history::round_secs(counted.as_secs(), self.config.round_log),
+            batch: self.batch.as_ref().map(|b| b.label.clone()),
+            interruptions: self.phase_interruptions,
+            note: self.phase_note.take(),
+            completed: true,
+        };
+        for observer in &mut self.observers {
+            observer.on_phase_complete(&record);
+        }
+        self.pending_records.push(record);
+
+        // 截止时刻过后不再开始新的阶段
+        self.workday_over |= self.past_until();
+        let finished = self.phase;
+        match self.phase {
+            Phase::Focus => {
+                self.completed_focus += 1;
+                if let Some(batch) = &mut self.batch {
+                    batch.sessions += 1;
+                    batch.focus += counted;
+                }
+                if let (Some(block), Some(size)) = (&mut self.work_block, self.config.block)
+                    && block.finished.is_none()
+                {
+                    block.sessions += 1;
+                    block.focus += counted;
+                    if block.sessions >= size {
+                        block.finished = Some(end);
+                    }
+                }
+                self.roll_day(end.date());
+                self.day_completed += 1;
+                self.week_completed += 1;
+                if self.day_completed == 1 {
+                    self.streak += 1;
+                }
+                let use_long = long_break_due(
+                    self.completed_focus,
+                    self.config.long_every,
+                    self.config.first_break_long,
+                );
+                self.phase = if use_long {
+                    Phase::LongBreak
+                } else {
+                    Phase::ShortBreak
+                };
+            }
+            Phase::ShortBreak | Phase::LongBreak => {
+                self.phase = Phase::Focus;
+            }
+        }
+        self.reset_current();
+        self.pick_message();
+        // 长休息之后、工作块完成后等待手动开始
+        if (self.config.pause_after_long && finished == Phase::LongBreak)
+            || self.finished_block().is_some()
+        {
+            self.ready_until = None;
+            self.running = false;
+        } else {
+            self.auto_start(); // 自动开始下一阶段
+        }
+    }
+
+    /// 跳过专注后总是短休息：长休息只在完成的专注数达到 `long_every` 时出现
+    fn advance_phase(&mut self) {
+        match self.phase {
+            Phase::Focus => {
+                self.phase = Phase::ShortBreak;
+            }
+            Phase::ShortBreak => {
+                self.phase = Phase::Focus;
+            }
+            Phase::LongBreak => {
+                self.phase = Phase::Focus;
+            }
+        }
+        self.reset_current();
+        self.pick_message();
+    }
+
+    fn formatted_remaining(&self) -> String {
+        match self.overtime {
+            Some(overtime) => format!("+{}", format_clock(overtime)),
+            None => format_clock(self.remaining),
+        }
+    }
+
+    fn formatted_elapsed(&self) -> String {
+        format_clock(self.total.saturating_sub(self.remaining))
+    }
+
+    /// 是否处于阶段的最后一分钟
+    fn in_final_minute(&self) -> bool {
+        self.config.final_warning
+            && !self.remaining.is_zero()
+            && self.remaining <= Duration::from_secs(60)
+    }
+
+    /// `--hide-time` 或 `v` 键：专注（含超时）中不显示数字，休息照常显示
+    fn time_hidden(&self) -> bool {
+        self.config.hide_time && self.phase == Phase::Focus
+    }
+
+    /// 最后几秒数字闪烁：按剩余时间每 `FINAL_BLINK` 切换一次，返回 `Some(是否处于暗的一拍)`
+    fn final_blink(&self) -> Option<bool> {
+        let counting_down = self.config.final_warning
+            && self.running
+            && !self.remaining.is_zero()
+            && self.remaining <= FINAL_SECONDS;
+        counting_down
+            .then(|| (self.remaining.as_millis() / FINAL_BLINK.as_millis()).is_multiple_of(2))
+    }
+
+    fn progress_ratio(&self) -> f64 {
+        if self.total.is_zero() {
+            return 0.0;
+        }
+        let elapsed = self.total.saturating_sub(self.remaining);
+        elapsed.as_secs_f64() / self.total.as_secs_f64()
+    }
+}
+
+/// 完成第 `completed` 个专注后是否该长休息；`first_long` 时第一个休息也是长休息，
+/// 之后仍按 `long_every` 的倍数。还没完成任何专注（0 是任何数的倍数）时不算
+fn long_break_due(completed: u32, long_every: u32, first_long: bool) -> bool {
+    completed > 0 && (completed.is_multiple_of(long_every) || (first_long && completed == 1))
+}
+
+fn preset_name(index: usize) -> &'static str {
+    if index == 0 { "A" } else { "B" }
+}
+
+fn format_clock(duration: Duration) -> String {
+    let total_secs = duration.as_secs();
+    let minutes = total_secs / 60;
+    let seconds = total_secs % 60;
+    format!("{:02}:{:02}", minutes, seconds)
+}
+
+/// 解析 `MM:SS`（或仅分钟 `MM`）
+fn parse_clock(text: &str) -> Option<Duration> {
+    let (minutes, seconds) = match text.split_once(':') {
+        Some((minutes, seconds)) => (minutes.parse::<u64>().ok()?, seconds.parse::<u64>().ok()?),
+        None => (text.parse::<u64>().ok()?, 0),
+    };
+    if seconds >= 60 {
+        return None;
+    }
+    let secs = minutes.checked_mul(60)?.checked_add(seconds)?;
+    Some(Duration::from_secs(secs))
+}
+
+/// 解析 `--until` 的 `HH:MM`
+fn parse_time_of_day(text: &str) -> Result<NaiveTime, String> {
+    NaiveTime::parse_from_str(text.trim(), "%H:%M")
+        .map_err(|_| format!("expected a time like 17:00, got `{text}`"))
+}
+
+/// 解析 `--min-focus-ratio` / `--max-focus-ratio`，必须是正数
+fn parse_ratio(text: &str) -> Result<f64, String> {
+    match text.trim().parse::<f64>() {
+        Ok(ratio) if ratio.is_finite() && ratio > 0.0 => Ok(ratio),
+        _ => Err(format!(
+            "expected a positive number like 2 or 4.5, got `{text}`"
+        )),
+    }
+}
+
+/// 下一次到达 `time` 的时刻：今天已经过了就是明天
+fn next_occurrence(time: NaiveTime, now: NaiveDateTime) -> NaiveDateTime {
+    let today = now.date().and_time(time);
+    if today > now {
+        today
+    } else {
+        today + TimeDelta::days(1)
+    }
+}
+
+/// 解析 `25m`、`90s`、`1h30m` 这类时长；不带单位的数字按分钟计。
+///
+/// 它直接处理命令行和 `--duration-source` 的外部输入，任何输入都只会返回错误而不会 panic：
+/// 所有乘法和加法都做溢出检查，超长的数字在 `parse` 时就会失败，超过 24h 的时长也是错误。
+/// 单位可以任意顺序、重复出现（`30m1h` 与 `1h30m` 相同），空串、只有单位或结尾缺单位的输入都是错误。
+fn parse_duration(text: &str) -> Result<Duration, String> {
+    let invalid = || format!("expected a duration like 25m, 90s or 1h30m, got `{text}`");
+    let at_most_a_day = |secs: u64| {
+        if secs > MAX_PHASE_MINUTES * 60 {
+            Err(format!("duration too large: `{text}` (at most 24h)"))
+        } else {
+            Ok(Duration::from_secs(secs))
+        }
+    };
+    let text = text.trim();
+    if let Ok(minutes) = text.parse::<u64>() {
+        return minutes
+            .checked_mul(60)
+            .ok_or_else(invalid)
+            .and_then(at_most_a_day);
+    }
+    let mut secs: u64 = 0;
+    let mut number = String::new();
+    for c in text.chars() {
+        if c.is_ascii_digit() {
+            number.push(c);
+            continue;
+        }
+        let unit = match c {
+            'h' => 3600,
+            'm' => 60,
+            's' => 1,
+            _ => return Err(invalid()),
+        };
+        let value: u64 = std::mem::take(&mut number).parse().map_err(|_| invalid())?;
+        secs = value
+            .checked_mul(unit)
+            .and_then(|v| secs.checked_add(v))
+            .ok_or_else(invalid)?;
+    }
+    if !number.is_empty() || text.is_empty() {
+        return Err(invalid());
+    }
+    at_most_a_day(secs)
+}
+
+/// `parse_duration` 的逆运算：`1h30m`、`25m`、`45s`，零为 `0s`；不足一秒的部分舍去
+fn format_duration(duration: Duration) -> String {
+    let secs = duration.as_secs();
+    let (hours, minutes, seconds) = (secs / 3600, secs / 60 % 60, secs % 60);
+    let mut out = String::new();
+    if hours > 0 {
+        out += &format!("{hours}h");
+    }
+    if minutes > 0 {
+        out += &format!("{minutes}m");
+    }
+    if seconds > 0 || out.is_empty() {
+        out += &format!("{seconds}s");
+    }
+    out
+}
+
+/// 标题栏计数超过上限时显示为 "99+"，完整数字见 `pomodoros stats`
+const HEADER_COUNT_CAP: u32 = 99;
+
+fn capped_count(count: u32) -> String {
+    if count > HEADER_COUNT_CAP {
+        format!("{HEADER_COUNT_CAP}+")
+    } else {
+        count.to_string()
+    }
+}
+
+/// 进度条文字按可用宽度收缩：先把分隔符缩成一个空格，再去掉百分比，剩余时间始终保留
+fn gauge_label(time: &str, sep: &str, percent: u16, width: u16) -> String {
+    let width = usize::from(width);
+    let full = format!("{time}{sep}{percent}%");
+    if full.chars().count() <= width {
+        return full;
+    }
+    let compact = format!("{time} {percent}%");
+    if compact.chars().count() <= width {
+        return compact;
+    }
+    time.to_string()
+}
+
+fn ui(frame: &mut ratatui::Frame, app: &PomodoroApp) {
+    let size = frame.size();
+
+    let layout_with = |gauge: u16| {
+        Layout::default()
+            .direction(Direction::Vertical)
+            .margin(1)
+            .constraints(
+                [
+                    Constraint::Length(5),     // header
+                    Constraint::Length(gauge), // gauge
+                    Constraint::Min(7),        // big timer
+                    Constraint::Length(3),     // help
+                ]
+                .as_ref(),
+            )
+            .split(size)
+    };
+    // 圆环画在计时器面板里，画不下时退回进度面板
+    let mut layout = layout_with(if app.config.ring { 0 } else { 4 });
+    let ring = app.config.ring
+        && !app.config.icons.ascii
+        && ring::Ring::fits(layout[2].inner(&Margin {
+            horizontal: 1,
+            vertical: 1,
+        }));
+    if app.config.ring && !ring {
+        layout = layout_with(4);
+    }
+
+    if app.config.ambient_bg {
+        frame.render_widget(
+            Block::default().style(Style::default().bg(app.phase.tint())),
+            size,
+        );
+    }
+
+    // Header
+    let theme = &app.config.theme;
+    let accent = theme.accent(app.phase);
+    let bar_color = theme.gauge_fg.unwrap_or(accent);
+    let icons = &app.config.icons;
+    let sep = if icons.ascii { "  |  " } else { "  ·  " };
+    let title = Line::from(vec![
+        Span::styled(
+            format!("{} ", icons.phase(app.phase)),
+            Style::default().fg(accent),
+        ),
+        Span::styled(
+            app.config.names.get(app.phase),
+            Style::default().fg(accent).add_modifier(Modifier::BOLD),
         ),
-        Span::raw("  ·  Completed "),
+        Span::raw(match app.config.count_scope {
+            config::CountScope::Run => format!("{sep}Completed "),
+            config::CountScope::Day => format!("{sep}Completed today "),
+            config::CountScope::Week => format!("{sep}Completed this week "),
+        }),
         Span::styled(
-            format!("{}", app.completed_focus),
+            capped_count(app.completed()),
             Style::default()
-                .fg(Color::Gray)
+                .fg(theme.muted)
                 .add_modifier(Modifier::BOLD),
         ),
-        Span::raw(""),
+        Span::raw(match app.skipped_focus {
+            0 => String::new(),
+            skipped => format!(", {} skipped", capped_count(skipped)),
+        }),
+        Span::raw(match app.config.daily_goal {
+            Some(goal) => format!(
+                "{sep}Today {}/{}{sep}Streak {}d",
+                capped_count(app.day_completed),
+                goal,
+                app.streak
+            ),
+            None => format!(
+                "{sep}Today {}{sep}Streak {}d",
+                capped_count(app.day_completed),
+                app.streak
+            ),
+        }),
+        Span::raw(match app.preset {
+            Some(index) => format!("{sep}Preset {}", preset_name(index)),
+            None => String::new(),
+        }),
+        Span::raw(match app.profile {
+            Some(index) => format!("{sep}Profile {}", app.config.profiles[index].0),
+            None => String::new(),
+        }),
+        Span::raw(match &app.batch {
+            Some(batch) => format!("{sep}Batch {} ({})", batch.label, batch.sessions),
+            None => String::new(),
+        }),
+        Span::raw(match app.phase_interruptions {
+            0 => String::new(),
+            _ if app.phase != Phase::Focus => String::new(),
+            count => format!("{sep}Interrupted {}", capped_count(count)),
+        }),
+        Span::raw(match (&app.work_block, app.config.block) {
+            (Some(block), Some(size)) => format!("{sep}Block {}/{size}", block.sessions),
+            _ => String::new(),
+        }),
     ]);
-    let header = Paragraph::new(title)
-        .block(
-            Block::default()
-                .borders(Borders::ALL)
-                .border_type(BorderType::Rounded)
-                .title("Status")
-                .title_alignment(Alignment::Center),
-        )
+    let mut header_block = style_for_phase(app).title("Status");
+    if app.config.show_clock {
+        header_block = header_block.title(
+            Title::from(Span::styled(
+                format!(" {} ", app.clock.local().format("%H:%M")),
+                Style::default().fg(theme.faint),
+            ))
+            .alignment(Alignment::Right),
+        );
+    }
+    let mut header_lines = vec![title];
+    if let Some(ratio) =
+        stats::focus_break_ratio(app.focused_total.as_secs(), app.break_total.as_secs())
+    {
+        header_lines.push(Line::from(Span::styled(
+            format!(
+                "Focus:Break {ratio}{sep}{} / {}",
+                timeline::format_secs(app.focused_total.as_secs()),
+                timeline::format_secs(app.break_total.as_secs())
+            ),
+            Style::default().fg(theme.faint),
+        )));
+    }
+    let header = Paragraph::new(header_lines)
+        .block(header_block)
         .alignment(Alignment::Center);
     frame.render_widget(header, layout[0]);
 
-    // Gauge
-    let percent = (app.progress_ratio() * 100.0) as u16;
-    let gauge = Gauge::default()
-        .block(
-            Block::default()
-                .borders(Borders::ALL)
-                .border_type(BorderType::Rounded)
-                .title("Progress")
-                .title_alignment(Alignment::Center),
-        )
-        .gauge_style(
-            Style::default()
-                .fg(accent)
-                .bg(Color::Black)
-                .add_modifier(Modifier::BOLD),
-        )
-        .label(Span::styled(
-            format!("{}  ·  {}%", app.formatted_remaining(), percent),
-            Style::default().fg(Color::White),
-        ))
-        .percent(percent);
-    frame.render_widget(gauge, layout[1]);
+    // Gauge
+    let elapsed_percent = (app.progress_ratio() * 100.0) as u16;
+    let percent = match app.config.gauge_direction {
+        config::GaugeDirection::Fill => elapsed_percent,
+        config::GaugeDirection::Drain => 100 - elapsed_percent.min(100),
+    };
+    let mut gauge_block = style_for_phase(app).title("Progress");
+    if app.in_final_minute() {
+        gauge_block = gauge_block.border_style(Style::default().fg(theme.warning));
+        if !icons.ascii {
+            gauge_block = gauge_block.border_type(BorderType::Thick);
+        }
+    }
+    match app.config.bar_style {
+        _ if ring => {}
+        config::BarStyle::Gauge => {
+            let label_width = gauge_block.inner(layout[1]).width;
+            let gauge = Gauge::default()
+                .block(gauge_block)
+                .gauge_style(
+                    Style::default()
+                        .fg(bar_color)
+                        .bg(theme.gauge_bg)
+                        .add_modifier(Modifier::BOLD),
+                )
+                .label(Span::styled(
+                    if app.time_hidden() {
+                        String::new()
+                    } else {
+                        gauge_label(&app.formatted_remaining(), sep, percent, label_width)
+                    },
+                    Style::default().fg(theme.text),
+                ))
+                .percent(percent);
+            frame.render_widget(gauge, layout[1]);
+        }
+        config::BarStyle::Split => {
+            let mut inner = gauge_block.inner(layout[1]);
+            frame.render_widget(gauge_block, layout[1]);
+            // 只画进度条那一行，不画下面的时间标签
+            if app.time_hidden() {
+                inner.height = inner.height.min(1);
+            }
+            let elapsed = app.formatted_elapsed();
+            let remaining = app.formatted_remaining();
+            frame.render_widget(
+                split_bar::SplitBar::new(
+                    app.progress_ratio(),
+                    &elapsed,
+                    &remaining,
+                    bar_color,
+                    theme,
+                ),
+                inner,
+            );
+        }
+    }
+
+    // Big timer text
+    let timer_lines = if let Some(alerting) = &app.alerting {
+        vec![
+            Line::from(Span::styled(
+                "Time's up",
+                Style::default().fg(accent).add_modifier(Modifier::BOLD),
+            )),
+            Line::from(Span::styled(
+                if alerting.start {
+                    format!("Press any key to start {}", app.config.names.get(app.phase))
+                } else {
+                    "Press any key".to_string()
+                },
+                Style::default().fg(theme.muted),
+            )),
+        ]
+    } else if let Some(ready) = app.ready_remaining() {
+        let secs = ready.as_secs() + u64::from(ready.subsec_nanos() > 0);
+        vec![
+            Line::from(Span::styled(
+                if app.warming_up {
+                    "Settle in"
+                } else {
+                    "Get ready to focus"
+                },
+                Style::default().fg(accent).add_modifier(Modifier::BOLD),
+            )),
+            Line::from(Span::styled(
+                format!(
+                    "Starting in {}{}{sep}any key to start now",
+                    secs,
+                    if icons.ascii { "..." } else { "…" }
+                ),
+                Style::default().fg(theme.muted),
+            )),
+        ]
+    } else {
+        let time_text = match &app.config.pause_unless_process {
+            _ if app.running && app.overtime.is_some() => {
+                format!("{} Overtime{sep}n: Finish", icons.running)
+            }
+            _ if app.running => format!("{} Running", icons.running),
+            Some(name) if app.auto_paused => format!("{} Waiting for {name}", icons.paused),
+            _ if app.idle_paused => format!("{} Paused while you're away", icons.paused),
+            _ => format!("{} Paused", icons.paused),
+        };
+        // 专注用粗体，休息用较淡的字重并加上休息图标
+        let mut digits_style = if app.phase == Phase::Focus {
+            Style::default()
+                .fg(if app.overtime.is_some() {
+                    theme.warning
+                } else {
+                    theme.text
+                })
+                .add_modifier(Modifier::BOLD)
+        } else {
+            Style::default().fg(theme.muted)
+        };
+        // 最后 5 秒红色与暗色交替
+        match app.final_blink() {
+            Some(true) => digits_style = digits_style.fg(theme.warning).add_modifier(Modifier::DIM),
+            Some(false) => digits_style = digits_style.fg(theme.warning),
+            None => {}
+        }
+        let time_line = if app.time_hidden() {
+            Line::from(Span::styled(
+                if icons.ascii {
+                    "Focusing..."
+                } else {
+                    "Focusing…"
+                },
+                Style::default().fg(theme.text).add_modifier(Modifier::BOLD),
+            ))
+        } else if app.phase == Phase::Focus {
+            Line::from(Span::styled(app.formatted_remaining(), digits_style))
+        } else {
+            Line::from(vec![
+                Span::styled(
+                    format!("{} ", icons.phase(app.phase)),
+                    Style::default().fg(accent),
+                ),
+                Span::styled(app.formatted_remaining(), digits_style),
+            ])
+        };
+        let mut status_style = Style::default().fg(theme.muted);
+        if !app.running && app.pause_dimmed() {
+            status_style = status_style.add_modifier(Modifier::DIM);
+        }
+        vec![time_line, Line::from(Span::styled(time_text, status_style))]
+    };
+    let timer_block = style_for_phase(app).title(
+        app.config
+            .phase_styles
+            .get(app.phase)
+            .and_then(|style| style.title.as_deref())
+            .unwrap_or("Timer"),
+    );
+    let mut timer_area = timer_block.inner(layout[2]);
+    frame.render_widget(timer_block, layout[2]);
+    if ring {
+        frame.render_widget(
+            ring::Ring::new(app.progress_ratio(), bar_color, theme.faint),
+            timer_area,
+        );
+        // 文字放在圆环正中
+        let lines = timer_lines.len() as u16;
+        timer_area.y += timer_area.height.saturating_sub(lines) / 2;
+        timer_area.height = lines.min(timer_area.height);
+    }
+    frame.render_widget(
+        Paragraph::new(timer_lines).alignment(Alignment::Center),
+        timer_area,
+    );
+    // 切换动画：强调色从左向右擦除
+    if let Some(progress) = app.transition_progress() {
+        let inner = layout[2].inner(&Margin {
+            horizontal: 1,
+            vertical: 1,
+        });
+        let offset = (f64::from(inner.width) * progress) as u16;
+        let wipe = Rect {
+            x: inner.x + offset,
+            width: inner.width - offset,
+            ..inner
+        };
+        frame
+            .buffer_mut()
+            .set_style(wipe, Style::default().bg(accent).fg(Color::Black));
+    }
+
+    // Help footer (依次优先：输入框、提示消息、激励语、快捷键)
+    let (footer_title, footer_line) = if let Some(prompt) = &app.prompt {
+        let mut spans = vec![
+            Span::styled(
+                format!("{}: ", prompt.kind.label()),
+                Style::default().fg(accent).add_modifier(Modifier::BOLD),
+            ),
+            Span::raw(prompt.input.as_str()),
+            Span::styled(
+                if icons.ascii { "_" } else { "█" },
+                Style::default().fg(theme.muted),
+            ),
+        ];
+        if let Some(error) = prompt.error {
+            spans.push(Span::styled(
+                format!("  {error}"),
+                Style::default().fg(theme.warning),
+            ));
+        }
+        let hint = if prompt.kind == PromptKind::ResetCount {
+            format!("y: Confirm{sep}Any other key: Cancel")
+        } else {
+            format!("Enter: Confirm{sep}Esc: Cancel")
+        };
+        (hint, Line::from(spans))
+    } else if let Some(notice) = app.current_notice() {
+        (
+            "Notice".to_string(),
+            Line::from(Span::styled(
+                notice,
+                Style::default().fg(theme.text).add_modifier(Modifier::BOLD),
+            )),
+        )
+    } else if let Some(message) = app.current_message() {
+        (
+            "Motivation".to_string(),
+            Line::from(Span::styled(
+                message,
+                Style::default().fg(accent).add_modifier(Modifier::ITALIC),
+            )),
+        )
+    } else {
+        (
+            "Shortcuts".to_string(),
+            Line::from(vec![
+                Span::raw(if icons.ascii { "" } else { "␣ " }),
+                Span::raw("Space: Start/Pause"),
+                Span::raw(sep),
+                Span::raw(if icons.ascii { "" } else { "⏭ " }),
+                Span::raw("n: Skip"),
+                Span::raw(sep),
+                Span::raw(if icons.ascii { "" } else { "⟲ " }),
+                Span::raw("r: Reset"),
+                Span::raw(sep),
+                Span::raw("q: Quit"),
+                Span::raw(sep),
+                Span::raw("?: Help"),
+            ]),
+        )
+    };
+    let channels = Title::from(Span::styled(
+        format!(" {} ", app.alert_channels().join(" ")),
+        Style::default().fg(theme.faint),
+    ))
+    .alignment(Alignment::Right);
+    let help = Paragraph::new(footer_line)
+        .block(style_for_phase(app).title(footer_title).title(channels))
+        .alignment(Alignment::Center);
+    frame.render_widget(Clear, layout[3]);
+    frame.render_widget(help, layout[3]);
+
+    if let Some(level) = app.battery_alert {
+        let area = centered_rect(size, 44, 6);
+        let warning = Paragraph::new(vec![
+            Line::from(Span::styled(
+                format!("🔋 Battery low ({level}%)"),
+                Style::default()
+                    .fg(theme.warning)
+                    .add_modifier(Modifier::BOLD),
+            )),
+            Line::from("Timer paused. Plug in, then"),
+            Line::from("press Space to resume."),
+        ])
+        .alignment(Alignment::Center)
+        .block(
+            style_for_phase(app)
+                .border_style(Style::default().fg(theme.warning))
+                .title("Warning"),
+        );
+        frame.render_widget(Clear, area);
+        frame.render_widget(warning, area);
+    }
+
+    if app.showing_done() {
+        let lines = done_lines(app, accent);
+        let area = centered_rect(size, 48, lines.len() as u16 + 2);
+        let done =
+            Paragraph::new(lines)
+                .alignment(Alignment::Center)
+                .block(style_for_phase(app).title(if app.workday_over {
+                    "Workday over"
+                } else {
+                    "Plan complete"
+                }));
+        frame.render_widget(Clear, area);
+        frame.render_widget(done, area);
+    }
+
+    if let Some(block) = app.finished_block() {
+        let lines = block_lines(block, app.config.numbers, theme, accent);
+        let area = centered_rect(size, 44, lines.len() as u16 + 2);
+        let summary = Paragraph::new(lines)
+            .alignment(Alignment::Center)
+            .block(style_for_phase(app).title("Block complete"));
+        frame.render_widget(Clear, area);
+        frame.render_widget(summary, area);
+    }
+
+    if let Some((summary, _)) = &app.splash {
+        let lines = splash_lines(summary, theme, accent);
+        let area = centered_rect(size, 44, lines.len() as u16 + 2);
+        let splash = Paragraph::new(lines)
+            .alignment(Alignment::Center)
+            .block(style_for_phase(app).title("Today"));
+        frame.render_widget(Clear, area);
+        frame.render_widget(splash, area);
+    }
+
+    if let Some(scroll) = app.help {
+        let lines = help_lines(app);
+        let area = centered_rect(size, 60, lines.len() as u16 + 2);
+        // 不让内容滚出可视区域
+        let visible = area.height.saturating_sub(2) as usize;
+        let scroll = scroll.min(lines.len().saturating_sub(visible) as u16);
+        let help = Paragraph::new(lines).scroll((scroll, 0)).block(
+            style_for_phase(app).title("Help").title(
+                Title::from(format!(
+                    "{}/{} scroll{sep}?/Esc close",
+                    actions::key_name(KeyCode::Up, icons.ascii),
+                    actions::key_name(KeyCode::Down, icons.ascii),
+                ))
+                .position(Position::Bottom),
+            ),
+        );
+        frame.render_widget(Clear, area);
+        frame.render_widget(help, area);
+    }
+
+    if app.flash_inverted() == Some(true) {
+        let area = frame.buffer_mut().area;
+        frame
+            .buffer_mut()
+            .set_style(area, Style::default().add_modifier(Modifier::REVERSED));
+    }
+
+    if icons.ascii {
+        asciify(frame.buffer_mut());
+    }
+}
+
+/// 计划完成画面：本次运行完成的专注数和专注时长
+fn done_lines(app: &PomodoroApp, accent: Color) -> Vec<Line<'static>> {
+    let theme = &app.config.theme;
+    let numbers = app.config.numbers;
+    vec![
+        Line::from(Span::styled(
+            "All done!",
+            Style::default().fg(accent).add_modifier(Modifier::BOLD),
+        )),
+        Line::from(format!(
+            "Completed {}, focused {}",
+            numbers.plural(app.completed_focus - app.run_baseline, "session"),
+            numbers.duration(app.focused_total.as_secs())
+        )),
+        Line::from(""),
+        Line::from(Span::styled(
+            "Press any key to quit",
+            Style::default().fg(theme.faint),
+        )),
+    ]
+}
+
+/// 工作块完成汇总：专注数、专注时长、中断次数和起止时间
+fn block_lines(
+    block: &WorkBlock,
+    numbers: numbers::NumberFormat,
+    theme: &theme::Theme,
+    accent: Color,
+) -> Vec<Line<'static>> {
+    let finished = block.finished.unwrap_or(block.started);
+    vec![
+        Line::from(Span::styled(
+            format!(
+                "{}, {} focus",
+                numbers.plural(block.sessions, "session"),
+                numbers.duration(block.focus.as_secs())
+            ),
+            Style::default().fg(accent).add_modifier(Modifier::BOLD),
+        )),
+        Line::from(numbers.plural(block.interruptions, "interruption")),
+        Line::from(format!(
+            "{} – {}",
+            block.started.format("%H:%M"),
+            finished.format("%H:%M")
+        )),
+        Line::from(""),
+        Line::from(Span::styled(
+            "w new block · q quit",
+            Style::default().fg(theme.faint),
+        )),
+    ]
+}
+
+/// 启动概况：今天的专注数、专注时长和每日目标进度
+fn splash_lines(
+    summary: &stats::DaySummary,
+    theme: &theme::Theme,
+    accent: Color,
+) -> Vec<Line<'static>> {
+    let numbers = summary.numbers;
+    let done = summary.totals.sessions;
+    if done == 0 {
+        return vec![
+            Line::from(Span::styled(
+                "Nothing logged yet today",
+                Style::default().fg(accent).add_modifier(Modifier::BOLD),
+            )),
+            Line::from("Every streak starts with one pomodoro."),
+        ];
+    }
+    let mut lines = vec![
+        Line::from(Span::styled(
+            numbers.plural(done, "session"),
+            Style::default().fg(accent).add_modifier(Modifier::BOLD),
+        )),
+        Line::from(format!(
+            "{} focus",
+            numbers.duration(summary.totals.focus_secs)
+        )),
+    ];
+    if let Some(goal) = summary.goal {
+        const WIDTH: u32 = 20;
+        let filled = (done.min(goal) * WIDTH).div_ceil(goal) as usize;
+        lines.push(Line::from(vec![
+            Span::styled("█".repeat(filled), Style::default().fg(accent)),
+            Span::styled(
+                "░".repeat(WIDTH as usize - filled),
+                Style::default().fg(theme.faint),
+            ),
+        ]));
+        lines.push(Line::from(if done >= goal {
+            format!(
+                "Goal {}/{} reached",
+                numbers.count(done.into()),
+                numbers.count(goal.into())
+            )
+        } else {
+            format!(
+                "Goal {}/{}, {} to go",
+                numbers.count(done.into()),
+                numbers.count(goal.into()),
+                numbers.count((goal - done).into())
+            )
+        }));
+    }
+    lines
+}
+
+/// 帮助面板内容：按分组列出按键表，再加上退出键等不在表中的固定按键
+fn help_lines(app: &PomodoroApp) -> Vec<Line<'static>> {
+    let key_color = app.config.theme.keys;
+    let ascii = app.config.icons.ascii;
+    let row = |keys: String, description: &str| {
+        Line::from(vec![
+            Span::styled(format!("  {keys:<14}"), Style::default().fg(key_color)),
+            Span::raw(description.to_string()),
+        ])
+    };
+    let mut lines = Vec::new();
+    for category in actions::Category::ALL {
+        if !lines.is_empty() {
+            lines.push(Line::from(""));
+        }
+        lines.push(Line::from(Span::styled(
+            category.title(),
+            Style::default().add_modifier(Modifier::BOLD),
+        )));
+        for binding in actions::BINDINGS.iter().filter(|b| b.category == category) {
+            let keys: Vec<String> = binding
+                .keys
+                .iter()
+                .map(|&code| actions::key_name(code, ascii))
+                .collect();
+            lines.push(row(keys.join(", "), binding.description));
+        }
+        for chord in actions::CHORDS.iter().filter(|c| c.category == category) {
+            let keys: Vec<String> = chord
+                .keys
+                .iter()
+                .map(|&code| actions::key_name(code, ascii))
+                .collect();
+            lines.push(row(keys.join(" "), chord.description));
+        }
+        if category == actions::Category::Navigation {
+            lines.push(row("Esc".to_string(), "Close a popup or prompt"));
+            if app.config.mouse {
+                lines.push(row("Click".to_string(), "Start / pause, or close a popup"));
+            }
+            let quit: Vec<String> = app
+                .config
+                .quit_keys
+                .iter()
+                .map(|key| key.name(ascii))
+                .collect();
+            lines.push(row(quit.join(", "), "Quit"));
+            lines.push(row("Ctrl+C".to_string(), "Quit (always)"));
+        }
+    }
+    lines
+}
+
+/// 把进度条等控件内置的 Unicode 字符换成 ASCII
+fn asciify(buf: &mut Buffer) {
+    for cell in &mut buf.content {
+        let ascii = match cell.symbol() {
+            "█" | "▉" | "▊" | "▋" | "▌" | "▍" | "▎" | "▏" => "#",
+            "┃" => "|",
+            "░" => ".",
+            _ => continue,
+        };
+        cell.set_symbol(ascii);
+    }
+}
+
+fn phase_border(app: &PomodoroApp) -> BorderType {
+    match app
+        .config
+        .phase_styles
+        .get(app.phase)
+        .and_then(|style| style.border)
+        .or_else(|| app.config.palette.border(app.phase))
+    {
+        Some(config::Border::Plain) => BorderType::Plain,
+        Some(config::Border::Thick) => BorderType::Thick,
+        Some(config::Border::Double) => BorderType::Double,
+        Some(config::Border::Rounded) | None => BorderType::Rounded,
+    }
+}
+
+/// `--ascii` 时的边框
+const ASCII_BORDER: border::Set = border::Set {
+    top_left: "+",
+    top_right: "+",
+    bottom_left: "+",
+    bottom_right: "+",
+    vertical_left: "|",
+    vertical_right: "|",
+    horizontal_top: "-",
+    horizontal_bottom: "-",
+};
+
+/// 当前阶段的面板样式（边框类型来自配置，默认圆角）
+fn style_for_phase(app: &PomodoroApp) -> Block<'static> {
+    let block = Block::default()
+        .borders(Borders::ALL)
+        .border_type(phase_border(app))
+        .title_alignment(Alignment::Center);
+    let block = if app.config.ambient_bg {
+        block.style(Style::default().bg(app.phase.tint()))
+    } else {
+        block
+    };
+    let block = match app.config.theme.border {
+        Some(color) => block.border_style(Style::default().fg(color)),
+        None => block,
+    };
+    if app.config.icons.ascii {
+        block.border_set(ASCII_BORDER)
+    } else {
+        block
+    }
+}
+
+/// 居中弹窗区域（不超过 `area`）
+fn centered_rect(area: Rect, width: u16, height: u16) -> Rect {
+    let width = width.min(area.width);
+    let height = height.min(area.height);
+    Rect {
+        x: area.x + (area.width - width) / 2,
+        y: area.y + (area.height - height) / 2,
+        width,
+        height,
+    }
+}
+
+/// 启动时确定的终端模式
+#[derive(Debug, Clone, Copy)]
+struct ScreenMode {
+    mouse: bool,
+    alternate: bool,
+}
+
+fn setup_terminal(
+    mode: ScreenMode,
+) -> Result<Terminal<ratatui::backend::CrosstermBackend<Stdout>>> {
+    let mut stdout = io::stdout();
+    enter_screen(&mut stdout, mode)?;
+    let backend = ratatui::backend::CrosstermBackend::new(stdout);
+    let mut terminal = Terminal::new(backend)?;
+    if !mode.alternate {
+        // 在主缓冲区绘制：先清屏，避免和之前的输出混在一起
+        terminal.clear()?;
+    }
+    Ok(terminal)
+}
+
+fn enter_screen(out: &mut impl io::Write, mode: ScreenMode) -> Result<()> {
+    enable_raw_mode()?;
+    if mode.alternate {
+        execute!(out, EnterAlternateScreen)?;
+    }
+    // 括号粘贴：粘贴内容整体作为一个事件到达，不会被当成快捷键
+    execute!(out, EnableBracketedPaste)?;
+    if mode.mouse {
+        execute!(out, EnableMouseCapture)?;
+    }
+    Ok(())
+}
+
+fn restore_terminal(
+    terminal: &mut Terminal<ratatui::backend::CrosstermBackend<Stdout>>,
+    mode: ScreenMode,
+) -> Result<()> {
+    disable_raw_mode()?;
+    if mode.mouse {
+        execute!(terminal.backend_mut(), DisableMouseCapture)?;
+    }
+    execute!(terminal.backend_mut(), DisableBracketedPaste)?;
+    if mode.alternate {
+        execute!(terminal.backend_mut(), LeaveAlternateScreen)?;
+    } else {
+        // 最后一帧留在滚动缓冲里，把光标移到它下面
+        let height = terminal.size()?.height;
+        terminal.set_cursor(0, height.saturating_sub(1))?;
+        io::Write::write_all(terminal.backend_mut(), b"\n")?;
+        io::Write::flush(terminal.backend_mut())?;
+    }
+    terminal.show_cursor()?;
+    Ok(())
+}
+
+/// 界面线程 panic 时先恢复终端再打印 panic 信息，否则终端会停在原始模式、
+/// 备用屏幕里且没有光标，信息也看不到。后台线程（如播放提示音）panic 不影响界面，不处理。
+fn install_panic_hook(mode: ScreenMode) {
+    let previous = std::panic::take_hook();
+    std::panic::set_hook(Box::new(move |info| {
+        if std::thread::current().name() == Some("main") {
+            let mut stdout = io::stdout();
+            let _ = disable_raw_mode();
+            if mode.mouse {
+                let _ = execute!(stdout, DisableMouseCapture);
+            }
+            let _ = execute!(stdout, DisableBracketedPaste);
+            if mode.alternate {
+                let _ = execute!(stdout, LeaveAlternateScreen);
+            }
+            let _ = execute!(stdout, crossterm::cursor::Show);
+        }
+        previous(info);
+    }));
+}
+
+/// 程序自身的事件处理：阶段到点时按 `--alert` 响铃、播放提示音或发通知
+fn add_default_observers(app: &mut PomodoroApp) {
+    let mut backends = app.config.alerts.clone();
+    if app.config.mute {
+        backends.remove(&config::AlertBackend::Bell);
+        backends.remove(&config::AlertBackend::Sound);
+    }
+    app.observe(Box::new(alert::Alerts {
+        backends,
+        cue: app.config.cue(),
+        names: app.config.names.clone(),
+    }));
+}
+
+/// 挂起界面打开编辑器，退出后重新进入界面并加载配置；出错只在页脚提示
+fn edit_config(
+    terminal: &mut Terminal<ratatui::backend::CrosstermBackend<Stdout>>,
+    mode: ScreenMode,
+    path: Option<&std::path::Path>,
+    reload: &dyn Fn() -> Result<PomodoroConfig>,
+    app: &mut PomodoroApp,
+) -> Result<()> {
+    let Some(path) = path else {
+        app.notice("No config file location on this system".to_string());
+        return Ok(());
+    };
+    restore_terminal(terminal, mode)?;
+    let edited = editor::open(path);
+    enter_screen(terminal.backend_mut(), mode)?;
+    terminal.clear()?;
+    match edited.and_then(|()| reload()) {
+        Ok(config) => {
+            app.reload_config(config);
+            // 提示音设置可能变了
+            app.observers.clear();
+            add_default_observers(app);
+        }
+        Err(err) => {
+            // toml 的错误信息跨多行，最后一行是具体原因，页脚只放它
+            let cause = err.root_cause().to_string();
+            let reason = cause.lines().rfind(|line| !line.trim().is_empty());
+            app.notice(format!(
+                "Config not reloaded: {}",
+                reason.unwrap_or_default().trim()
+            ));
+        }
+    }
+    Ok(())
+}
+
+/// 命令行显式传入的值优先，其次是配置文件，最后是命令行默认值
+fn pick<T>(matches: &ArgMatches, id: &str, cli: T, file: Option<T>) -> T {
+    match matches.value_source(id) {
+        Some(ValueSource::CommandLine) => cli,
+        _ => file.unwrap_or(cli),
+    }
+}
+
+/// 以分钟给出的阶段时长最多一天
+const MAX_PHASE_MINUTES: u64 = 24 * 60;
+
+/// 分钟转 `Duration`，超过一天（包括乘法会溢出的数）时报错而不是 panic
+fn minutes(value: u64, what: &str) -> Result<Duration> {
+    if value > MAX_PHASE_MINUTES {
+        anyhow::bail!(
+            "{what} duration too large: {value} minutes (at most {MAX_PHASE_MINUTES}, i.e. 24h)"
+        );
+    }
+    value
+        .checked_mul(60)
+        .map(Duration::from_secs)
+        .ok_or_else(|| anyhow::anyhow!("{what} duration too large: {value} minutes"))
+}
+
+/// 专注和休息的时长：除了 `minutes` 的检查，还不能为零
+fn phase_minutes(value: u64, what: &str) -> Result<Duration> {
+    if value == 0 {
+        anyhow::bail!("{what} duration must be at least 1 minute");
+    }
+    minutes(value, what)
+}
+
+/// 各阶段的显示名称，命令行优先于配置文件
+fn phase_names(args: &CliArgs, file: &config::FileConfig) -> config::PhaseNames {
+    config::PhaseNames::new(
+        args.focus_name.as_deref().or(file.focus_name.as_deref()),
+        args.short_name.as_deref().or(file.short_name.as_deref()),
+        args.long_name.as_deref().or(file.long_name.as_deref()),
+    )
+}
+
+fn number_format(args: &CliArgs, file: &config::FileConfig) -> numbers::NumberFormat {
+    numbers::NumberFormat::detect(args.locale.as_deref().or(file.locale.as_deref()))
+}
+
+/// 0 表示未设置目标
+fn daily_goal(args: &CliArgs, matches: &ArgMatches, file: &config::FileConfig) -> Option<u32> {
+    pick(
+        matches,
+        "daily_goal",
+        args.daily_goal,
+        file.daily_goal.map(Some),
+    )
+    .filter(|goal| *goal > 0)
+}
+
+fn debt_rules(
+    args: &CliArgs,
+    matches: &ArgMatches,
+    file: &config::FileConfig,
+) -> Option<stats::DebtRules> {
+    let track = pick(matches, "track_debt", args.track_debt, file.track_debt);
+    let goal = daily_goal(args, matches, file)?;
+    track.then(|| stats::DebtRules {
+        goal,
+        cap: file.debt_cap.unwrap_or(goal.saturating_mul(2)),
+        skip_weekends: file.debt_skip_weekends.unwrap_or(true),
+        window_days: file.debt_window_days.unwrap_or(7),
+    })
+}
+
+fn build_config(
+    args: &CliArgs,
+    matches: &ArgMatches,
+    file: &config::FileConfig,
+) -> Result<PomodoroConfig> {
+    let focus_minutes = pick(
+        matches,
+        "focus_minutes",
+        args.focus_minutes,
+        file.focus_minutes,
+    );
+    let short_break_minutes = pick(
+        matches,
+        "short_break_minutes",
+        args.short_break_minutes,
+        file.short_break_minutes,
+    );
+    let long_break_minutes = pick(
+        matches,
+        "long_break_minutes",
+        args.long_break_minutes,
+        file.long_break_minutes,
+    );
+    let ready_countdown_secs = pick(
+        matches,
+        "ready_countdown_secs",
+        args.ready_countdown_secs,
+        file.ready_countdown_secs,
+    );
+    let final_warning = pick(
+        matches,
+        "no_final_warning",
+        !args.no_final_warning,
+        file.final_warning,
+    );
+    // 预设里没写的时长沿用平时的设置
+    let preset = |set: Option<&config::PresetMinutes>| -> Result<Preset> {
+        let set = set.cloned().unwrap_or_default();
+        Ok(Preset {
+            focus: phase_minutes(set.focus_minutes.unwrap_or(focus_minutes), "focus")?,
+            short_break: phase_minutes(
+                set.short_break_minutes.unwrap_or(short_break_minutes),
+                "short break",
+            )?,
+            long_break: phase_minutes(
+                set.long_break_minutes.unwrap_or(long_break_minutes),
+                "long break",
+            )?,
+        })
+    };
+    let presets = match &file.preset {
+        Some(presets) => Some([preset(presets.a.as_ref())?, preset(presets.b.as_ref())?]),
+        None => None,
+    };
+    let profiles = file
+        .profiles
+        .iter()
+        .flatten()
+        .map(|(name, set)| Ok((name.clone(), preset(Some(set))?)))
+        .collect::<Result<Vec<_>>>()?;
+    let palette = pick(matches, "palette", args.palette, file.palette);
+    let sound = args.sound.clone().or_else(|| file.sound.clone());
+    let tone = args.tone.or(file.tone);
+    let mut alerts: BTreeSet<config::AlertBackend> =
+        match args.alert.clone().or_else(|| file.alert.clone()) {
+            Some(list) => list.into_iter().collect(),
+            // 没有指定时沿用单独的开关：设了提示音就播放，否则响铃
+            None if sound.is_some() || tone.is_some() => [config::AlertBackend::Sound].into(),
+            None => [config::AlertBackend::Bell].into(),
+        };
+    if pick(matches, "visual_bell", args.visual_bell, file.visual_bell) {
+        alerts.insert(config::AlertBackend::Flash);
+    }
+    Ok(PomodoroConfig {
+        // 以秒为单位的时长只来自命令行，给时优先于分钟
+        focus: match args.focus_seconds {
+            Some(secs) => Duration::from_secs(secs),
+            None => phase_minutes(focus_minutes, "focus")?,
+        },
+        short_break: match args.short_seconds {
+            Some(secs) => Duration::from_secs(secs),
+            None => phase_minutes(short_break_minutes, "short break")?,
+        },
+        long_break: match args.long_seconds {
+            Some(secs) => Duration::from_secs(secs),
+            None => phase_minutes(long_break_minutes, "long break")?,
+        },
+        presets,
+        profiles,
+        long_every: pick(matches, "long_every", args.long_every, file.long_every),
+        first_break_long: pick(
+            matches,
+            "focus_first_break_long",
+            args.focus_first_break_long,
+            file.focus_first_break_long,
+        ),
+        start_phase: args
+            .start_phase
+            .or(file.start_phase)
+            .map(config::StartPhase::phase),
+        mute: pick(matches, "mute", args.mute, file.mute),
+        alerts,
+        sound,
+        fade_in: Duration::from_millis(pick(
+            matches,
+            "fade_in_ms",
+            args.fade_in_ms,
+            file.fade_in_ms,
+        )),
+        tone,
+        volume: pick(matches, "volume", args.volume, file.volume).clamp(1, 100),
+        ready_countdown: Duration::from_secs(ready_countdown_secs),
+        warmup: Duration::from_secs(pick(
+            matches,
+            "warmup_secs",
+            args.warmup_secs,
+            file.warmup_secs,
+        )),
+        final_warning,
+        messages: file.messages.clone().unwrap_or_default(),
+        message_order: file.message_order.unwrap_or_default(),
+        pause_unless_process: args.pause_unless_process.clone(),
+        pause_on_low_battery: args.pause_on_low_battery,
+        pause_on_audio_change: args.pause_on_audio_change,
+        auto_pause_idle: args.auto_pause_idle_secs.map(Duration::from_secs),
+        media_control: pick(
+            matches,
+            "media_control",
+            args.media_control,
+            file.media_control,
+        ),
+        media_follow_pause: pick(
+            matches,
+            "media_follow_pause",
+            args.media_follow_pause,
+            file.media_follow_pause,
+        ),
+        dnd: pick(matches, "dnd", args.dnd, file.dnd),
+        dnd_on_cmd: args.dnd_on_cmd.clone().or_else(|| file.dnd_on_cmd.clone()),
+        dnd_off_cmd: args
+            .dnd_off_cmd
+            .clone()
+            .or_else(|| file.dnd_off_cmd.clone()),
+        live_notify: pick(matches, "live_notify", args.live_notify, file.live_notify),
+        mouse: pick(matches, "no_mouse", !args.no_mouse, file.mouse),
+        alternate_screen: pick(
+            matches,
+            "no_alternate_screen",
+            !args.no_alternate_screen,
+            file.alternate_screen,
+        ),
+        splash: pick(matches, "no_splash", !args.no_splash, file.splash),
+        done_screen: pick(
+            matches,
+            "no_done_screen",
+            !args.no_done_screen,
+            file.done_screen,
+        ),
+        animations: pick(
+            matches,
+            "no_animations",
+            !args.no_animations,
+            file.animations,
+        ),
+        ambient_bg: pick(matches, "ambient_bg", args.ambient_bg, file.ambient_bg),
+        resume_remaining: pick(
+            matches,
+            "resume_remaining",
+            args.resume_remaining,
+            file.resume_remaining,
+        ),
+        pause_after_long: pick(
+            matches,
+            "pause_after_long",
+            args.pause_after_long,
+            file.pause_after_long,
+        ),
+        overrun: pick(matches, "overrun", args.overrun, file.overrun),
+        alert_until_ack: pick(
+            matches,
+            "alert_until_ack",
+            args.alert_until_ack,
+            file.alert_until_ack,
+        ),
+        min_break: Duration::from_secs(pick(
+            matches,
+            "min_break_secs",
+            args.min_break_secs,
+            file.min_break_secs,
+        )),
+        confirm_skip: Duration::from_millis(pick(
+            matches,
+            "confirm_skip_ms",
+            args.confirm_skip_ms,
+            file.confirm_skip_ms,
+        )),
+        zero_behavior: pick(
+            matches,
+            "zero_behavior",
+            args.zero_behavior,
+            file.zero_behavior,
+        ),
+        phase_styles: file.phase_style.clone().unwrap_or_default(),
+        icons: {
+            let icons = config::Icons::preset(pick(matches, "ascii", args.ascii, file.ascii));
+            match palette {
+                config::Palette::Colorblind => icons.distinct_breaks(),
+                config::Palette::Default => icons,
+            }
+            .with_overrides(&file.icons.clone().unwrap_or_default())
+        },
+        names: phase_names(args, file),
+        numbers: number_format(args, file),
+        palette,
+        theme: match args.theme_file.as_ref().or(file.theme_file.as_ref()) {
+            Some(path) => theme::load(path, palette)?,
+            None => theme::Theme::new(palette),
+        },
+        bar_style: pick(matches, "bar_style", args.bar_style, file.bar_style),
+        ring: pick(matches, "ring", args.ring, file.ring),
+        gauge_direction: pick(
+            matches,
+            "gauge_direction",
+            args.gauge_direction,
+            file.gauge_direction,
+        ),
+        count_scope: pick(matches, "count_scope", args.count_scope, file.count_scope),
+        auto_quit_idle: minutes(
+            pick(
+                matches,
+                "auto_quit_idle_minutes",
+                args.auto_quit_idle_minutes,
+                file.auto_quit_idle_minutes,
+            ),
+            "auto-quit idle",
+        )?,
+        quit_keys: match &file.quit_keys {
+            Some(names) => keys::parse_all(names).context("invalid `quit_keys` in config")?,
+            None => keys::default_quit_keys(),
+        },
+        show_clock: pick(matches, "show_clock", args.show_clock, file.show_clock),
+        hide_time: pick(matches, "hide_time", args.hide_time, file.hide_time),
+        daily_goal: daily_goal(args, matches, file),
+        round_log: minutes(
+            pick(
+                matches,
+                "round_log_minutes",
+                args.round_log_minutes,
+                file.round_log_minutes,
+            ),
+            "log rounding",
+        )?,
+        completed: args.completed,
+        max_sessions: if args.once {
+            Some(1)
+        } else {
+            args.max_sessions
+        },
+        block: args.block,
+        until: args
+            .until
+            .map(|time| next_occurrence(time, Local::now().naive_local())),
+    })
+}
+
+/// 退出码：完成计划或正常退出为 0，其余见 README
+const EXIT_ERROR: u8 = 1;
+const EXIT_CONFIG: u8 = 2;
+const EXIT_QUIT_EARLY: u8 = 3;
 
-    // Big timer text
-    let time_text = if app.running {
-        "⏱ Running"
-    } else {
-        "⏸ Paused"
+/// 主循环的结束原因
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Outcome {
+    Finished,
+    QuitEarly,
+}
+
+struct Setup {
+    config: PomodoroConfig,
+    /// `e` 键编辑的配置文件
+    config_path: Option<PathBuf>,
+    /// 按命令行参数重新读取配置文件
+    reload: Box<dyn Fn() -> Result<PomodoroConfig>>,
+    tick: Duration,
+    /// 两次重绘之间的最短间隔（`--max-fps`）
+    frame: Duration,
+    history_path: PathBuf,
+    state_path: PathBuf,
+    saved: Option<state::SavedState>,
+    resume: bool,
+    foreground: Option<mpsc::Receiver<bool>>,
+    battery: Option<mpsc::Receiver<u8>>,
+    audio: Option<mpsc::Receiver<()>>,
+    idle: Option<mpsc::Receiver<bool>>,
+    durations: Option<mpsc::Receiver<durations::Line>>,
+    splash: Option<stats::DaySummary>,
+    /// `--count-scope week` 时历史里本周已完成的专注
+    week_completed: Option<u32>,
+    /// 专注与短休息的比例超出范围时的提示，启动后显示在界面上
+    ratio_warning: Option<String>,
+    /// `--db` 的 SQLite 数据库
+    db: Option<db::Database>,
+}
+
+fn history_path(args: &CliArgs) -> Result<PathBuf> {
+    match &args.history {
+        Some(path) => Ok(path.clone()),
+        None => history::default_path()
+            .ok_or_else(|| anyhow::anyhow!("cannot determine the data directory")),
+    }
+}
+
+fn state_path(args: &CliArgs) -> Result<PathBuf> {
+    match &args.state_file {
+        Some(path) => Ok(path.clone()),
+        None => state::default_path()
+            .ok_or_else(|| anyhow::anyhow!("cannot determine the data directory")),
+    }
+}
+
+/// `--export-state` / `--import-state`
+fn share_state(args: &CliArgs) -> Result<()> {
+    let config_path = args.config.clone().or_else(config::default_path);
+    let state_path = state_path(args)?;
+    match &args.import_state {
+        Some(text) => share::import(text, config_path.as_deref(), &state_path),
+        None => {
+            println!("{}", share::export(config_path.as_deref(), &state_path)?);
+            Ok(())
+        }
+    }
+}
+
+fn run_command(args: &CliArgs, matches: &ArgMatches, command: &Command) -> Result<()> {
+    let history_path = history_path(args)?;
+    let today = Local::now().date_naive();
+    match command {
+        Command::Timeline { date, gap_minutes } => {
+            let file = config::load(args.config.as_deref())?;
+            timeline::run(
+                &history_path,
+                date.unwrap_or(today),
+                *gap_minutes,
+                &phase_names(args, &file),
+            )
+        }
+        Command::Stats => {
+            let file = config::load(args.config.as_deref())?;
+            let saved = state::load(&state_path(args)?)?;
+            stats::run(
+                &history_path,
+                saved.as_ref(),
+                daily_goal(args, matches, &file),
+                debt_rules(args, matches, &file),
+                today,
+                number_format(args, &file),
+                pick(matches, "ascii", args.ascii, file.ascii),
+            )
+        }
+        Command::Graph { days } => {
+            let file = config::load(args.config.as_deref())?;
+            let ascii = pick(matches, "ascii", args.ascii, file.ascii);
+            graph::run(&history_path, today, *days, ascii)
+        }
+        Command::ResetDebt => {
+            let path = state_path(args)?;
+            let mut saved = state::load(&path)?.unwrap_or_default();
+            saved.debt_reset = Some(today);
+            state::save(&path, &saved)?;
+            println!("Goal debt reset; counting starts again on {today}.");
+            Ok(())
+        }
+        Command::Status { format } => {
+            let path = state_path(args)?;
+            let saved = state::load(&path)?;
+            let file = config::load(args.config.as_deref())?;
+            status::run(
+                &status::path_for(&path),
+                saved.as_ref(),
+                format,
+                &phase_names(args, &file),
+            )
+        }
+        Command::Report { format, date, out } => {
+            let file = config::load(args.config.as_deref())?;
+            report::run(
+                &history_path,
+                *date,
+                *format,
+                out.as_deref(),
+                number_format(args, &file),
+            )
+        }
+        Command::Export {
+            format,
+            from,
+            to,
+            email,
+            out,
+        } => export::run(&history_path, *format, *from, *to, email, out.as_deref()),
+        Command::Replay { date } => {
+            let file = config::load(args.config.as_deref())?;
+            replay::run(
+                &history_path,
+                *date,
+                &phase_names(args, &file),
+                number_format(args, &file),
+            )
+        }
+        Command::Inspect { last: _, index } => inspect::run(
+            &history_path,
+            index.map(|n| usize::try_from(n).unwrap_or(usize::MAX)),
+        ),
+        Command::Summary { date } => {
+            let file = config::load(args.config.as_deref())?;
+            let saved = state::load(&state_path(args)?)?;
+            stats::run_summary(
+                &history_path,
+                saved.as_ref(),
+                daily_goal(args, matches, &file),
+                date.unwrap_or(today),
+                number_format(args, &file),
+            )
+        }
+        Command::Wait { focus, quiet } => {
+            let file = config::load(args.config.as_deref())?;
+            let config = build_config(args, matches, &file)?;
+            wait::run(config, *focus, *quiet)
+        }
+        Command::Simulate { hours, step_ms } => {
+            let file = config::load(args.config.as_deref())?;
+            let config = build_config(args, matches, &file)?;
+            simulate::run(config, *hours, Duration::from_millis(*step_ms))
+        }
+        Command::TestBell => {
+            let file = config::load(args.config.as_deref())?;
+            let config = build_config(args, matches, &file)?;
+            test_alerts(&config);
+            Ok(())
+        }
+    }
+}
+
+/// `test-bell`：按配置的提醒方式逐个触发一次，每种方式打印一行结果
+fn test_alerts(config: &PomodoroConfig) {
+    if config.alerts.is_empty() {
+        println!("No alerts are enabled (--alert); nothing was played.");
+        return;
+    }
+    let terminal = io::stdout().is_terminal();
+    let mut rang = false;
+    // 和 `alert::sound` 一样，响铃和提示音的回退合起来只响一次
+    let mut ring = || {
+        if !terminal {
+            "stdout is not a terminal; the bell can only ring in an interactive terminal."
+        } else if rang {
+            "the terminal bell already rang."
+        } else {
+            alert::ring();
+            rang = true;
+            "rang the terminal bell (BEL, \\x07)."
+        }
     };
-    let timer_lines = vec![
-        Line::from(Span::styled(
-            app.formatted_remaining(),
-            Style::default()
-                .fg(Color::White)
-                .add_modifier(Modifier::BOLD),
-        )),
-        Line::from(Span::styled(time_text, Style::default().fg(Color::Gray))),
-    ];
-    let timer = Paragraph::new(timer_lines)
-        .alignment(Alignment::Center)
-        .block(
-            Block::default()
-                .borders(Borders::ALL)
-                .border_type(BorderType::Rounded)
-                .title("Timer")
-                .title_alignment(Alignment::Center),
+    let cue = config.cue();
+    for backend in &config.alerts {
+        match backend {
+            // 和计时时一样，静音只关掉出声的两种
+            config::AlertBackend::Bell | config::AlertBackend::Sound if config.mute => println!(
+                "{}: muted (--mute or `mute = true`); nothing was played.",
+                backend.name()
+            ),
+            config::AlertBackend::Bell => println!("bell: {}", ring()),
+            config::AlertBackend::Sound => {
+                let failed = if let Some(sound) = &cue.sound {
+                    match alert::play_file(sound, cue.fade_in, cue.volume) {
+                        Ok(()) => {
+                            println!("sound: played {}.", sound.display());
+                            continue;
+                        }
+                        Err(err) => format!("couldn't play {}: {err}", sound.display()),
+                    }
+                } else if let Some(tone) = cue.tone {
+                    let name = tone.name();
+                    match alert::play_tone(tone, cue.volume) {
+                        Ok(()) => {
+                            println!("sound: played the built-in `{name}` tone.");
+                            continue;
+                        }
+                        Err(err) => format!("couldn't play the `{name}` tone: {err}"),
+                    }
+                } else {
+                    "no --sound file or --tone is set".to_string()
+                };
+                println!("sound: {failed}; falling back: {}", ring());
+            }
+            config::AlertBackend::Notify => {
+                let (title, body) = alert::time_up_message(&config.names, Phase::Focus);
+                if notify::send(&title, body) {
+                    println!("notify: sent a desktop notification.");
+                } else {
+                    println!(
+                        "notify: couldn't send a desktop notification (needs notify-send on Linux or osascript on macOS)."
+                    );
+                }
+            }
+            config::AlertBackend::Flash => {
+                if terminal {
+                    alert::flash(FLASH_STEP, FLASH_COUNT);
+                    println!("flash: flashed the screen {FLASH_COUNT} times.");
+                } else {
+                    println!("flash: stdout is not a terminal; there is no screen to flash.");
+                }
+            }
+        }
+    }
+    if rang {
+        println!(
+            "Heard nothing? Check your terminal's bell setting (audible vs. visual bell) and that it isn't muted by tmux/screen."
         );
-    frame.render_widget(timer, layout[2]);
-
-    // Help footer
-    let help = Paragraph::new(Line::from(vec![
-        Span::raw("␣ Space: Start/Pause  ·  "),
-        Span::raw("⏭ n: Skip  ·  "),
-        Span::raw("⟲ r: Reset  ·  "),
-        Span::raw("q: Quit"),
-    ]))
-    .block(
-        Block::default()
-            .borders(Borders::ALL)
-            .border_type(BorderType::Rounded)
-            .title("Shortcuts")
-            .title_alignment(Alignment::Center),
-    )
-    .alignment(Alignment::Center);
-    frame.render_widget(Clear, layout[3]);
-    frame.render_widget(help, layout[3]);
+    }
 }
 
-fn setup_terminal() -> Result<Terminal<ratatui::backend::CrosstermBackend<Stdout>>> {
-    enable_raw_mode()?;
-    let mut stdout = io::stdout();
-    execute!(stdout, EnterAlternateScreen, EnableMouseCapture)?;
-    let backend = ratatui::backend::CrosstermBackend::new(stdout);
-    let terminal = Terminal::new(backend)?;
-    Ok(terminal)
+fn truecolor() -> bool {
+    std::env::var("COLORTERM").is_ok_and(|value| matches!(value.as_str(), "truecolor" | "24bit"))
 }
 
-fn restore_terminal(
-    mut terminal: Terminal<ratatui::backend::CrosstermBackend<Stdout>>,
+/// 专注时长是短休息的多少倍；超出 `min..=max` 时返回提示。只是提醒，不阻止启动；
+/// 不到一分钟的专注是在试用或测试，不检查
+fn ratio_warning(config: &PomodoroConfig, min: f64, max: f64) -> Option<String> {
+    let focus = config.focus.as_secs();
+    let rest = config.short_break.as_secs();
+    let ratio = stats::focus_break_ratio(focus, rest)?;
+    if (min..=max).contains(&(focus as f64 / rest as f64)) {
+        return None;
+    }
+    Some(format!(
+        "focus:break ratio {ratio} ({} / {}) is outside {min}:1–{max}:1",
+        timeline::format_secs(focus),
+        timeline::format_secs(rest),
+    ))
+}
+
+fn prepare(args: &CliArgs, matches: &ArgMatches) -> Result<Setup> {
+    let history_path = history_path(args)?;
+    let state_path = state_path(args)?;
+    let saved = state::load(&state_path)?;
+    let file = config::load(args.config.as_deref())?;
+    let mut config = build_config(args, matches, &file)?;
+    let tick = Duration::from_millis(pick(matches, "tick_ms", args.tick_ms, file.tick_ms));
+    let max_fps = pick(matches, "max_fps", args.max_fps, file.max_fps).clamp(1, 240);
+    let frame = Duration::from_secs(1) / u32::from(max_fps);
+
+    // 只在支持 24 位色的终端上染色，其余终端上近似色会很刺眼
+    if config.ambient_bg && !truecolor() {
+        config.ambient_bg = false;
+    }
+    if config.dnd {
+        let defaults = dnd::default_commands();
+        config.dnd_on_cmd = config
+            .dnd_on_cmd
+            .take()
+            .or_else(|| defaults.map(|(on, _)| on.to_string()));
+        config.dnd_off_cmd = config
+            .dnd_off_cmd
+            .take()
+            .or_else(|| defaults.map(|(_, off)| off.to_string()));
+        if config.dnd_on_cmd.is_none() || config.dnd_off_cmd.is_none() {
+            eprintln!(
+                "warning: no default do-not-disturb commands here; set --dnd-on-cmd and --dnd-off-cmd"
+            );
+            config.dnd = false;
+        }
+    }
+    if config.sound.is_some() && !cfg!(feature = "sound") {
+        eprintln!("warning: built without the `sound` feature; --sound falls back to the bell");
+    } else if config.tone.is_some() && !cfg!(feature = "sound") {
+        eprintln!("warning: built without the `sound` feature; --tone falls back to the bell");
+    }
+
+    let foreground = config.pause_unless_process.as_deref().and_then(|name| {
+        let watcher = foreground::watch(name);
+        if watcher.is_none() {
+            eprintln!(
+                "warning: foreground window detection is not available here; ignoring --pause-unless-process"
+            );
+        }
+        watcher
+    });
+    if foreground.is_none() {
+        config.pause_unless_process = None;
+    }
+
+    let battery = config.pause_on_low_battery.and_then(|_| {
+        let watcher = battery::watch();
+        if watcher.is_none() {
+            eprintln!("note: no battery found; ignoring --pause-on-low-battery");
+        }
+        watcher
+    });
+    if battery.is_none() {
+        config.pause_on_low_battery = None;
+    }
+
+    let audio = config.pause_on_audio_change.then(audio::watch).flatten();
+    if config.pause_on_audio_change && audio.is_none() {
+        let hint = if cfg!(target_os = "macos") && !cfg!(feature = "audio-change") {
+            " (build with `--features audio-change`)"
+        } else {
+            ""
+        };
+        eprintln!(
+            "warning: audio output change detection is only available on macOS{hint}; ignoring --pause-on-audio-change"
+        );
+        config.pause_on_audio_change = false;
+    }
+
+    let idle = config.auto_pause_idle.and_then(|after| {
+        let watcher = idle::watch(after);
+        if watcher.is_none() {
+            let hint = if cfg!(feature = "idle") {
+                ""
+            } else {
+                " (build with `--features idle`)"
+            };
+            eprintln!(
+                "warning: system idle detection is not available here{hint}; ignoring --auto-pause-idle"
+            );
+        }
+        watcher
+    });
+    if idle.is_none() {
+        config.auto_pause_idle = None;
+    }
+
+    let source = args
+        .duration_source
+        .clone()
+        .or_else(|| file.duration_source.clone());
+    if let Some(source) = &source
+        && !source.exists()
+    {
+        eprintln!(
+            "note: {} doesn't exist; focus uses the default length",
+            source.display()
+        );
+    }
+    let durations = source.as_deref().map(durations::watch);
+
+    let db = match args.db.clone().or_else(|| file.db.clone()) {
+        Some(_) if !cfg!(feature = "sqlite") => {
+            eprintln!("warning: built without the `sqlite` feature; ignoring --db");
+            None
+        }
+        Some(path) => Some(db::Database::open(&path)?),
+        None => None,
+    };
+
+    let min_ratio = pick(
+        matches,
+        "min_focus_ratio",
+        args.min_focus_ratio,
+        file.min_focus_ratio,
+    );
+    let max_ratio = pick(
+        matches,
+        "max_focus_ratio",
+        args.max_focus_ratio,
+        file.max_focus_ratio,
+    );
+    if !(min_ratio > 0.0 && min_ratio <= max_ratio) {
+        anyhow::bail!(
+            "the focus ratio range {min_ratio}–{max_ratio} is invalid; the minimum must be positive and not above the maximum"
+        );
+    }
+    let ratio_warning = (!args.force)
+        .then(|| ratio_warning(&config, min_ratio, max_ratio))
+        .flatten();
+    if let Some(warning) = &ratio_warning {
+        eprintln!("warning: {warning}; pass --force to skip this check");
+    }
+
+    let migrated = history::migrate(&history_path)?;
+    if migrated > 0 {
+        eprintln!(
+            "Migrated {migrated} history records to timestamps with a time zone offset \
+             (the originals are kept as .csv.bak)"
+        );
+    }
+
+    // 概况和本周计数只是提示：历史文件读不了就当作还没有记录
+    let week_scope = config.count_scope == config::CountScope::Week;
+    let records = if config.splash || week_scope {
+        history::load(&history_path).unwrap_or_default()
+    } else {
+        Vec::new()
+    };
+    let today = Local::now().date_naive();
+    let splash = config
+        .splash
+        .then(|| stats::day_summary(&records, today, None, config.daily_goal, config.numbers));
+    let week_completed = week_scope.then(|| stats::week_sessions(&records, today));
+
+    let reload_args = args.clone();
+    let reload_matches = matches.clone();
+    let reload = Box::new(move || {
+        let file = config::load(reload_args.config.as_deref())?;
+        let mut config = build_config(&reload_args, &reload_matches, &file)?;
+        config.ambient_bg &= truecolor();
+        Ok(config)
+    });
+
+    Ok(Setup {
+        config,
+        config_path: args.config.clone().or_else(config::default_path),
+        reload,
+        tick,
+        frame,
+        history_path,
+        state_path,
+        saved,
+        resume: !args.fresh,
+        foreground,
+        battery,
+        audio,
+        idle,
+        durations,
+        splash,
+        week_completed,
+        ratio_warning,
+        db,
+    })
+}
+
+/// 把待写的阶段记录（同时写入 `--db`）和批次追加到历史文件
+fn write_history(
+    app: &mut PomodoroApp,
+    history_path: &std::path::Path,
+    db: Option<&db::Database>,
 ) -> Result<()> {
-    disable_raw_mode()?;
-    execute!(
-        terminal.backend_mut(),
-        LeaveAlternateScreen,
-        DisableMouseCapture
-    )?;
-    terminal.show_cursor()?;
+    for record in app.pending_records.drain(..) {
+        history::append(history_path, &record)?;
+        if let Some(db) = db {
+            db.insert(&record)?;
+        }
+    }
+    for record in app.pending_batches.drain(..) {
+        history::append_batch(&history::batch_path(history_path), &record)?;
+    }
     Ok(())
 }
 
-fn main() -> Result<()> {
-    let args = CliArgs::parse();
-    let config = PomodoroConfig {
-        focus: Duration::from_secs(args.focus_minutes * 60),
-        short_break: Duration::from_secs(args.short_break_minutes * 60),
-        long_break: Duration::from_secs(args.long_break_minutes * 60),
-        long_every: args.long_every,
-        mute: args.mute,
+fn run_tui(setup: Setup) -> Result<Outcome> {
+    let Setup {
+        config,
+        config_path,
+        reload,
+        tick,
+        frame,
+        history_path,
+        state_path,
+        saved,
+        resume,
+        foreground,
+        battery,
+        audio,
+        idle,
+        durations,
+        splash,
+        week_completed,
+        ratio_warning,
+        db,
+    } = setup;
+    let mode = ScreenMode {
+        mouse: config.mouse,
+        alternate: config.alternate_screen,
     };
-    let tick = Duration::from_millis(args.tick_ms);
+    // SIGTERM 和退出键一样正常收尾：恢复终端、记下进行中的阶段、保存状态
+    let terminated = Arc::new(AtomicBool::new(false));
+    #[cfg(unix)]
+    signal_hook::flag::register(signal_hook::consts::SIGTERM, Arc::clone(&terminated))?;
 
-    let mut terminal = setup_terminal()?;
+    let mut terminal = setup_terminal(mode)?;
+    install_panic_hook(mode);
     let mut app = PomodoroApp::new(config);
+    add_default_observers(&mut app);
+    if let Some(saved) = &saved {
+        app.restore(saved, resume);
+    }
+    // 历史里已经包含今天之前（和其他实例）完成的专注，在恢复之后设置，跨周的重置不会清掉它
+    if let Some(count) = week_completed {
+        app.week_completed = count;
+    }
+    if let Some(warning) = ratio_warning {
+        app.notice(format!("Note: {warning}"));
+    }
+    if let Some(summary) = splash {
+        app.show_splash(summary);
+    }
+    let mut media = app.config.media_control.then(media::MediaControl::new);
+    let mut live_notify = app
+        .config
+        .live_notify
+        .then(|| notify::LiveNotification::start(app.config.names.clone()));
+    let mut dnd = match (&app.config.dnd_on_cmd, &app.config.dnd_off_cmd) {
+        (Some(on), Some(off)) if app.config.dnd => {
+            Some(dnd::DoNotDisturb::new(on.clone(), off.clone()))
+        }
+        _ => None,
+    };
+
+    let status_path = status::path_for(&state_path);
+    let mut last_publish: Option<Instant> = None;
+    let mut recorded_goal: Option<(NaiveDate, u32)> = None;
 
     let mut last_redraw = Instant::now();
-    loop {
+    // 出错时也跳出循环，先收尾（恢复终端、勿扰和媒体，保存状态）再返回错误
+    let outcome: Result<Outcome> = loop {
         // 处理输入事件
-        if event::poll(tick)? {
-            if let Event::Key(key) = event::read()? {
-                if key.kind == KeyEventKind::Press {
+        // 动画期间缩短轮询间隔以保证流畅
+        let poll = if app.transition_progress().is_some() || app.flash_inverted().is_some() {
+            tick.min(frame)
+        } else {
+            tick
+        };
+        let event = match event::poll(poll).and_then(|ready| ready.then(event::read).transpose()) {
+            Ok(event) => event,
+            Err(err) => break Err(err.into()),
+        };
+        if let Some(event) = event {
+            match event {
+                Event::Key(key) if key.kind == KeyEventKind::Press => {
+                    app.touch();
                     match key.code {
-                        KeyCode::Char('q') | KeyCode::Esc | KeyCode::Char('c')
-                            if key.modifiers.contains(KeyModifiers::CONTROL) =>
-                        {
-                            break;
+                        _ if keys::is_interrupt(&key) => break Ok(app.quit_outcome()),
+                        _ if app.showing_done() => break Ok(Outcome::Finished),
+                        _ if app.prompt.is_some() => app.prompt_input(key.code),
+                        _ if app.help.is_some() => app.help_input(key.code),
+                        // Esc 先关闭弹窗或取消没按完的组合键，都没有时才按退出键处理
+                        KeyCode::Esc if app.pending_chord.take().is_some() => {}
+                        KeyCode::Esc if app.close_popup() => {}
+                        _ if app.config.quit_keys.iter().any(|k| k.matches(&key)) => {
+                            break Ok(app.quit_outcome());
+                        }
+                        _ if app.splash.is_some() => app.splash = None,
+                        // 工作块完成后只接受开始新块
+                        KeyCode::Char('w') | KeyCode::Enter if app.finished_block().is_some() => {
+                            app.new_block();
                         }
-                        KeyCode::Char(' ') => app.toggle(),
-                        KeyCode::Char('n') | KeyCode::Right => app.skip(),
-                        KeyCode::Char('r') => app.reset_current(),
-                        KeyCode::Char('q') => break,
-                        _ => {}
+                        _ if app.finished_block().is_some() => {}
+                        _ if app.alerting.is_some() => app.acknowledge(),
+                        // 就绪倒计时中任意键立即开始
+                        _ if app.ready_until.is_some() => app.start_ready(),
+                        code => app.press(code),
                     }
                 }
+                Event::Paste(text) => {
+                    app.touch();
+                    app.paste(&text);
+                }
+                Event::Mouse(mouse_event)
+                    if mouse_event.kind == MouseEventKind::Down(MouseButton::Left) =>
+                {
+                    app.touch();
+                    app.click();
+                }
+                _ => {}
+            }
+        }
+
+        if std::mem::take(&mut app.edit_requested)
+            && let Err(err) = edit_config(
+                &mut terminal,
+                mode,
+                config_path.as_deref(),
+                &reload,
+                &mut app,
+            )
+        {
+            break Err(err);
+        }
+
+        if let Some(foreground) = &foreground {
+            while let Ok(focused) = foreground.try_recv() {
+                app.set_foreground(focused);
+            }
+        }
+        if let Some(battery) = &battery {
+            while let Ok(level) = battery.try_recv() {
+                app.set_battery(level);
+            }
+        }
+        if let Some(audio) = &audio {
+            while audio.try_recv().is_ok() {
+                app.audio_changed();
+            }
+        }
+        if let Some(durations) = &durations {
+            while let Ok(line) = durations.try_recv() {
+                app.queue_focus(line);
+            }
+        }
+        if let Some(idle) = &idle {
+            while let Ok(away) = idle.try_recv() {
+                app.set_idle(away);
             }
         }
 
+        if terminated.load(Ordering::Relaxed) {
+            break Ok(app.quit_outcome());
+        }
+
         // 更新状态
         app.update();
+        // 写文件失败不中断计时，只在页脚提示
+        // 每天第一次运行时记下当天的目标，`stats` 据此判断那天是否达标
+        if let Some(goal) = app.config.daily_goal {
+            let today = app.clock.local().date();
+            if recorded_goal != Some((today, goal)) {
+                recorded_goal = Some((today, goal));
+                if let Err(err) =
+                    history::record_goal(&history::goal_path(&history_path), today, goal)
+                {
+                    app.notice(format!("Couldn't save the goal: {err:#}"));
+                }
+            }
+        }
+        let finished_phase = !app.pending_records.is_empty();
+        if let Err(err) = write_history(&mut app, &history_path, db.as_ref()) {
+            app.notice(format!("Couldn't save history: {err:#}"));
+        }
+        if finished_phase && let Err(err) = state::save(&state_path, &app.snapshot()) {
+            app.notice(format!("Couldn't save state: {err:#}"));
+        }
+        if let Some(media) = &mut media {
+            media.sync(
+                app.phase,
+                app.phase_started.is_some(),
+                app.running,
+                app.config.media_follow_pause,
+            );
+        }
+        if let Some(dnd) = &mut dnd {
+            // 专注开始计时后（含中途暂停）保持勿扰，跳过或进入休息时立即关闭
+            dnd.sync(app.phase == Phase::Focus && app.phase_started.is_some());
+        }
+        if let Some(live_notify) = &mut live_notify {
+            live_notify.update(app.phase, app.remaining, app.running);
+        }
+        if app.plan_complete() {
+            if !app.config.done_screen {
+                break Ok(Outcome::Finished);
+            }
+            // 停在汇总画面，已自动开始的下一阶段不再计时
+            app.running = false;
+            app.ready_until = None;
+        }
+        if app.idle_expired() {
+            break Ok(app.quit_outcome());
+        }
+
+        // 每秒发布一次实时状态；状态栏只是辅助功能，写入失败不影响计时
+        if last_publish.is_none_or(|at| at.elapsed() >= Duration::from_secs(1)) {
+            let _ = status::publish(&status_path, &app.live_status());
+            last_publish = Some(Instant::now());
+        }
 
         // 绘制
-        if last_redraw.elapsed() >= Duration::from_millis(16) {
-            // ~60FPS 上限
-            terminal.draw(|f| ui(f, &app))?;
+        // 重绘频率上限（`--max-fps`，默认约 60FPS）
+        if last_redraw.elapsed() >= frame {
+            if let Err(err) = terminal.draw(|f| ui(f, &app)) {
+                break Err(err.into());
+            }
             last_redraw = Instant::now();
         }
+    };
+
+    let restored = restore_terminal(&mut terminal, mode);
+    // 终端已恢复，换回默认的 panic 处理
+    drop(std::panic::take_hook());
+    status::clear(&status_path);
+    if let Some(media) = &mut media {
+        media.restore();
+    }
+    if let Some(dnd) = &mut dnd {
+        dnd.restore();
+    }
+    if let Some(live_notify) = live_notify {
+        live_notify.close();
+    }
+    app.record_partial();
+    // 退出时结束进行中的批次
+    app.end_batch();
+    // 历史写入失败时仍然保存状态，之后再依次报告错误
+    let written = write_history(&mut app, &history_path, db.as_ref());
+    let saved = state::save(&state_path, &app.snapshot());
+    let outcome = outcome?;
+    restored?;
+    written?;
+    saved?;
+
+    // 计划完成时回顾当天
+    if app.plan_complete() {
+        let records = history::load(&history_path)?;
+        let summary = stats::day_summary(
+            &records,
+            app.day,
+            Some(app.day_interruptions),
+            app.config.daily_goal,
+            app.config.numbers,
+        );
+        print!("{}", summary.recap());
+        let title = if app.workday_over {
+            "Workday over"
+        } else {
+            "Pomodoro plan complete"
+        };
+        notify::send(title, &summary.headline());
     }
+    Ok(outcome)
+}
 
-    restore_terminal(terminal)?;
+/// 界面必须画在终端里；输出被重定向时给出可读的提示，而不是 crossterm 的底层错误
+fn require_terminal() -> Result<()> {
+    if !io::stdout().is_terminal() {
+        anyhow::bail!(
+            "stdout is not a terminal, so the timer can't be shown\n\
+             For scripts use `pomodoros wait`, `pomodoros status` or `pomodoros simulate`"
+        );
+    }
     Ok(())
 }
+
+fn fail(err: &anyhow::Error, code: u8) -> ExitCode {
+    eprintln!("Error: {err:?}");
+    ExitCode::from(code)
+}
+
+fn main() -> ExitCode {
+    let matches = CliArgs::command().get_matches();
+    let args = CliArgs::from_arg_matches(&matches).unwrap_or_else(|err| err.exit());
+
+    if args.export_state || args.import_state.is_some() {
+        return match share_state(&args) {
+            Ok(()) => ExitCode::SUCCESS,
+            Err(err) => fail(&err, EXIT_ERROR),
+        };
+    }
+    let result = match &args.command {
+        Some(command) => run_command(&args, &matches, command).map(|()| Outcome::Finished),
+        None => match prepare(&args, &matches) {
+            Ok(setup) => require_terminal().and_then(|()| run_tui(setup)),
+            Err(err) => return fail(&err, EXIT_CONFIG),
+        },
+    };
+    match result {
+        Ok(Outcome::Finished) => ExitCode::SUCCESS,
+        Ok(Outcome::QuitEarly) => ExitCode::from(EXIT_QUIT_EARLY),
+        Err(err) => fail(&err, EXIT_ERROR),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::clock::ManualClock;
+
+    /// 只按命令行参数构造配置，不读配置文件
+    pub(crate) fn test_config(args: &[&str]) -> PomodoroConfig {
+        let matches = CliArgs::command()
+            .try_get_matches_from(std::iter::once("pomodoros").chain(args.iter().copied()))
+            .unwrap();
+        let cli = CliArgs::from_arg_matches(&matches).unwrap();
+        let mut config = build_config(&cli, &matches, &config::FileConfig::default()).unwrap();
+        config.mute = true;
+        config.animations = false;
+        config
+    }
+
+    /// 用手动时钟运行的应用；返回的时钟与应用共用同一时间
+    fn test_app(args: &[&str]) -> (PomodoroApp, ManualClock) {
+        let start = NaiveDate::from_ymd_opt(2024, 3, 4)
+            .and_then(|day| day.and_hms_opt(9, 0, 0))
+            .unwrap();
+        let clock = ManualClock::new(start);
+        let app = PomodoroApp::with_clock(test_config(args), Box::new(clock.clone()));
+        (app, clock)
+    }
+
+    /// 推进时钟并刷新一次
+    fn tick(app: &mut PomodoroApp, clock: &ManualClock, by: Duration) {
+        clock.advance(by);
+        app.update();
+    }
+
+    fn logged_phases(app: &PomodoroApp) -> Vec<Phase> {
+        app.pending_records
+            .iter()
+            .map(|record| record.phase)
+            .collect()
+    }
+
+    #[test]
+    fn manual_clock_drives_each_phase_boundary() {
+        let (mut app, clock) = test_app(&[
+            "--focus-seconds",
+            "10",
+            "--short-seconds",
+            "5",
+            "--long-seconds",
+            "7",
+            "-e",
+            "2",
+        ]);
+        app.toggle();
+        let cycle = [
+            (Phase::Focus, 10, Phase::ShortBreak),
+            (Phase::ShortBreak, 5, Phase::Focus),
+            (Phase::Focus, 10, Phase::LongBreak),
+            (Phase::LongBreak, 7, Phase::Focus),
+        ];
+        for (phase, secs, next) in cycle {
+            assert_eq!(app.phase, phase);
+            assert_eq!(app.total, Duration::from_secs(secs));
+            // 差 1 毫秒时还停在本阶段
+            tick(
+                &mut app,
+                &clock,
+                Duration::from_secs(secs) - Duration::from_millis(1),
+            );
+            assert_eq!(app.phase, phase);
+            assert_eq!(app.remaining, Duration::from_millis(1));
+            // 到点：`on_finish` 记录本阶段并自动开始下一阶段
+            tick(&mut app, &clock, Duration::from_millis(1));
+            assert_eq!(app.phase, next, "after {phase:?}");
+            assert_eq!(app.remaining, app.total);
+            assert!(app.running);
+        }
+        assert_eq!(app.completed_focus, 2);
+        assert_eq!(
+            logged_phases(&app),
+            [
+                Phase::Focus,
+                Phase::ShortBreak,
+                Phase::Focus,
+                Phase::LongBreak
+            ]
+        );
+        assert!(app.pending_records.iter().all(|record| record.completed));
+        assert_eq!(app.pending_records[0].duration_secs, 10);
+    }
+
+    #[test]
+    fn manual_clock_does_not_move_a_paused_timer() {
+        let (mut app, clock) = test_app(&["--focus-seconds", "10"]);
+        tick(&mut app, &clock, Duration::from_secs(60));
+        assert_eq!(app.remaining, Duration::from_secs(10));
+        app.toggle();
+        tick(&mut app, &clock, Duration::from_secs(4));
+        app.toggle();
+        tick(&mut app, &clock, Duration::from_secs(60));
+        assert_eq!(app.phase, Phase::Focus);
+        assert_eq!(app.remaining, Duration::from_secs(6));
+        assert!(app.pending_records.is_empty());
+    }
+
+    #[test]
+    fn skips_are_counted_apart_from_finished_sessions() {
+        let (mut app, clock) = test_app(&[
+            "--focus-seconds",
+            "10",
+            "--short-seconds",
+            "5",
+            "--long-seconds",
+            "7",
+            "-e",
+            "2",
+        ]);
+        app.toggle();
+        tick(&mut app, &clock, Duration::from_secs(10));
+        tick(&mut app, &clock, Duration::from_secs(5));
+        assert_eq!((app.phase, app.completed_focus), (Phase::Focus, 1));
+
+        // 计时中途跳过：只记跳过数，之后总是短休息
+        tick(&mut app, &clock, Duration::from_secs(4));
+        app.skip();
+        assert_eq!(app.phase, Phase::ShortBreak);
+        assert_eq!((app.completed_focus, app.skipped_focus), (1, 1));
+        assert!(app.running);
+        app.skip();
+        assert_eq!(app.phase, Phase::Focus);
+
+        // 第二个完成的专注才轮到长休息，跳过的那次不算进节奏
+        tick(&mut app, &clock, Duration::from_secs(10));
+        assert_eq!(app.phase, Phase::LongBreak);
+        assert_eq!((app.completed_focus, app.skipped_focus), (2, 1));
+        tick(&mut app, &clock, Duration::from_secs(7));
+        tick(&mut app, &clock, Duration::from_secs(10));
+        assert_eq!(app.phase, Phase::ShortBreak);
+        assert_eq!(app.completed_focus, 3);
+        // 跳过的阶段不写历史
+        assert_eq!(
+            logged_phases(&app),
+            [
+                Phase::Focus,
+                Phase::ShortBreak,
+                Phase::Focus,
+                Phase::LongBreak,
+                Phase::Focus
+            ]
+        );
+    }
+
+    #[test]
+    fn first_break_long_then_normal_cadence() {
+        let due = |every, first_long| -> Vec<bool> {
+            (0..=8)
+                .map(|count| long_break_due(count, every, first_long))
+                .collect()
+        };
+        let (t, f) = (true, false);
+        assert_eq!(due(4, true), [f, t, f, f, t, f, f, f, t]);
+        assert_eq!(due(4, false), [f, f, f, f, t, f, f, f, t]);
+        assert_eq!(due(1, true), [f, t, t, t, t, t, t, t, t]);
+
+        // 状态机里：第一个休息是长休息，之后每 3 个一次
+        let (mut app, clock) = test_app(&[
+            "--focus-seconds",
+            "10",
+            "--short-seconds",
+            "5",
+            "--long-seconds",
+            "7",
+            "-e",
+            "3",
+            "--focus-first-break-long",
+        ]);
+        app.toggle();
+        let mut breaks = Vec::new();
+        for _ in 0..6 {
+            let focus = app.remaining;
+            tick(&mut app, &clock, focus);
+            breaks.push(app.phase);
+            let rest = app.remaining;
+            tick(&mut app, &clock, rest);
+        }
+        use Phase::{LongBreak as L, ShortBreak as S};
+        assert_eq!(breaks, [L, S, L, S, S, L]);
+    }
+
+    #[test]
+    fn gauge_label_shrinks_to_fit() {
+        let label = |width| gauge_label("12:34", "  ·  ", 88, width);
+        assert_eq!(label(40), "12:34  ·  88%");
+        assert_eq!(label(13), "12:34  ·  88%");
+        assert_eq!(label(10), "12:34 88%");
+        assert_eq!(label(5), "12:34");
+        // 比时间还窄时仍只给时间，由 ratatui 截断
+        assert_eq!(label(0), "12:34");
+    }
+
+    #[test]
+    fn ui_draws_on_narrow_terminals() {
+        use ratatui::backend::TestBackend;
+
+        let (mut app, clock) = test_app(&["--focus-seconds", "754"]);
+        app.toggle();
+        tick(&mut app, &clock, Duration::from_secs(90));
+        for width in [5, 10, 40] {
+            for height in [5, 12, 30] {
+                let mut terminal = Terminal::new(TestBackend::new(width, height)).unwrap();
+                terminal.draw(|f| ui(f, &app)).unwrap();
+            }
+        }
+        let mut terminal = Terminal::new(TestBackend::new(40, 30)).unwrap();
+        terminal.draw(|f| ui(f, &app)).unwrap();
+        let screen: String = terminal
+            .backend()
+            .buffer()
+            .content()
+            .iter()
+            .map(|cell| cell.symbol())
+            .collect();
+        assert!(screen.contains("11:04"), "{screen}");
+    }
+
+    #[test]
+    fn restart_resumes_the_exact_remaining_time() {
+        let (mut app, clock) = test_app(&["--focus-seconds", "20"]);
+        app.toggle();
+        tick(&mut app, &clock, Duration::from_millis(7_266));
+        assert_eq!(app.remaining, Duration::from_millis(12_734));
+        let saved = app.snapshot();
+
+        let (mut restored, _clock) = test_app(&["--focus-seconds", "20"]);
+        restored.restore(&saved, true);
+        assert_eq!(restored.remaining, Duration::from_millis(12_734));
+        assert_eq!(restored.total, Duration::from_secs(20));
+    }
+
+    #[test]
+    fn zero_behavior_finish_logs_only_time_run() {
+        let args = ["--focus-seconds", "60", "--zero-behavior", "finish"];
+        // 从没开始过的专注被设到 00:00：照常完成，但记录的时长为零
+        let (mut app, clock) = test_app(&args);
+        app.go_to(Duration::ZERO);
+        tick(&mut app, &clock, Duration::from_secs(1));
+        assert_eq!(app.phase, Phase::ShortBreak);
+        assert!(!app.running);
+        assert_eq!(app.completed_focus, 1);
+        let record = &app.pending_records[0];
+        assert!(record.completed);
+        assert_eq!(record.duration_secs, 0);
+        assert_eq!(record.start, record.end);
+
+        // 计时 3 秒后暂停再设到 00:00：只记这 3 秒
+        let (mut app, clock) = test_app(&args);
+        app.toggle();
+        tick(&mut app, &clock, Duration::from_secs(3));
+        app.toggle();
+        app.go_to(Duration::ZERO);
+        tick(&mut app, &clock, Duration::from_secs(1));
+        assert_eq!(app.phase, Phase::ShortBreak);
+        assert!(!app.running);
+        assert_eq!(app.pending_records[0].duration_secs, 3);
+    }
+
+    #[test]
+    fn zero_behavior_hold_waits_for_resume() {
+        let (mut app, clock) = test_app(&["--focus-seconds", "60", "--zero-behavior", "hold"]);
+        app.toggle();
+        tick(&mut app, &clock, Duration::from_secs(5));
+        app.toggle();
+        app.go_to(Duration::ZERO);
+        tick(&mut app, &clock, Duration::from_secs(30));
+        assert_eq!(app.phase, Phase::Focus);
+        assert_eq!(app.remaining, Duration::ZERO);
+        assert!(app.pending_records.is_empty());
+        // 继续计时后立即完成，暂停的 30 秒不算
+        app.toggle();
+        tick(&mut app, &clock, Duration::from_millis(100));
+        assert_eq!(app.phase, Phase::ShortBreak);
+        assert!(app.running);
+        assert_eq!(app.pending_records[0].duration_secs, 5);
+        assert!(app.pending_records[0].completed);
+    }
+
+    #[test]
+    fn minutes_rejects_overflow() {
+        let err = minutes(u64::MAX, "focus").unwrap_err();
+        assert!(
+            err.to_string().contains("focus duration too large"),
+            "{err}"
+        );
+        assert!(minutes(u64::MAX / 60 + 1, "focus").is_err());
+    }
+
+    #[test]
+    fn minutes_accepts_up_to_a_day() {
+        assert_eq!(
+            minutes(MAX_PHASE_MINUTES, "focus").unwrap(),
+            Duration::from_secs(24 * 3600)
+        );
+        assert_eq!(minutes(25, "focus").unwrap(), Duration::from_secs(1500));
+        assert!(minutes(MAX_PHASE_MINUTES + 1, "long break").is_err());
+    }
+
+    #[test]
+    fn phase_minutes_rejects_zero() {
+        let err = phase_minutes(0, "short break").unwrap_err();
+        assert!(err.to_string().contains("short break"), "{err}");
+        // 其他以分钟计的设置用 0 表示关闭
+        assert_eq!(minutes(0, "auto-quit idle").unwrap(), Duration::ZERO);
+    }
+
+    #[test]
+    fn parse_duration_rejects_bad_input() {
+        for text in [
+            "",
+            " ",
+            "abc",
+            "-5m",
+            "m",
+            "25x",
+            "1h30",
+            "1.5h",
+            "99999999999h",
+            "18446744073709551616",
+            "99999999999999999999s",
+            "5m\u{0}",
+            "２５m",
+        ] {
+            assert!(parse_duration(text).is_err(), "`{text}` should be rejected");
+        }
+    }
+
+    /// 固定种子的 xorshift，让“随机”输入每次都一样、失败时可以复现
+    fn xorshift(state: &mut u64) -> u64 {
+        *state ^= *state << 13;
+        *state ^= *state >> 7;
+        *state ^= *state << 17;
+        *state
+    }
+
+    #[test]
+    fn parse_duration_never_panics() {
+        const ALPHABET: &[char] = &[
+            '0', '1', '5', '9', 'h', 'm', 's', 'H', ' ', '-', '+', '.', ':', 'é', '\u{0}',
+        ];
+        let mut state = 0x9E37_79B9_7F4A_7C15;
+        for _ in 0..50_000 {
+            let len = xorshift(&mut state) % 24;
+            let text: String = (0..len)
+                .map(|_| ALPHABET[(xorshift(&mut state) % ALPHABET.len() as u64) as usize])
+                .collect();
+            let _ = parse_duration(&text);
+        }
+        for digits in 1..=40 {
+            let _ = parse_duration(&format!("{}h", "9".repeat(digits)));
+            let _ = parse_duration(&"9".repeat(digits));
+        }
+    }
+
+    #[test]
+    fn format_duration_round_trips() {
+        for secs in 0..=MAX_PHASE_MINUTES * 60 {
+            let duration = Duration::from_secs(secs);
+            let text = format_duration(duration);
+            assert_eq!(
+                parse_duration(&text),
+                Ok(duration),
+                "{secs}s formatted as `{text}`"
+            );
+        }
+    }
+
+    #[test]
+    fn parse_duration_accepts_units_in_any_order() {
+        assert_eq!(parse_duration("25"), Ok(Duration::from_secs(25 * 60)));
+        assert_eq!(parse_duration(" 90s "), Ok(Duration::from_secs(90)));
+        assert_eq!(parse_duration("1h30m"), Ok(Duration::from_secs(5400)));
+        assert_eq!(parse_duration("30m1h"), Ok(Duration::from_secs(5400)));
+        assert_eq!(parse_duration("24h"), Ok(Duration::from_secs(86_400)));
+    }
+}
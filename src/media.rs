@@ -0,0 +1,194 @@
+#[cfg(any(target_os = "linux", target_os = "macos"))]
+use std::process::{Command, Stdio};
+use std::sync::mpsc::{self, Receiver, Sender};
+use std::thread::{self, JoinHandle};
+
+use crate::Phase;
+
+/// 休息时暂停、专注时恢复媒体播放（Linux 走 MPRIS DBus，macOS 走 AppleScript）。
+/// 只恢复由我们暂停的播放；没有播放器时什么也不做。
+/// 暂停前先查询每个播放器的状态，只暂停正在播放的，恢复时也只对这些发送播放。
+/// 用 Play/Pause 而不是 PlayPause：切换命令会把期间被用户暂停的播放器又打开。
+#[derive(Debug)]
+pub struct MediaControl {
+    paused_by_us: bool,
+    worker: Option<(Sender<Action>, JoinHandle<()>)>,
+}
+
+impl MediaControl {
+    /// 命令在后台线程上按顺序执行，避免阻塞界面，也保证恢复一定在暂停之后
+    pub fn new() -> Self {
+        let (tx, rx) = mpsc::channel();
+        let handle = thread::spawn(move || work(rx));
+        Self {
+            paused_by_us: false,
+            worker: Some((tx, handle)),
+        }
+    }
+
+    /// 每帧调用，只在目标状态变化时发送命令。开启 `follow_pause` 后：
+    ///
+    /// ```text
+    /// 专注·未开始 ──Space──▶ 专注·计时 ──Space──▶ 专注·暂停 ──Space──▶ 专注·计时
+    ///   (不动)                (播放)               (暂停)               (播放)
+    ///                            │ 时间到 / n
+    ///                            ▼
+    ///                        休息·任意 (暂停) ──时间到 / n──▶ 专注·…
+    /// ```
+    ///
+    /// 未开启时只看阶段：专注播放，休息暂停。
+    pub fn sync(&mut self, phase: Phase, started: bool, running: bool, follow_pause: bool) {
+        match phase {
+            Phase::ShortBreak | Phase::LongBreak => self.pause(),
+            Phase::Focus if follow_pause && started && !running => self.pause(),
+            Phase::Focus => self.resume(),
+        }
+    }
+
+    pub fn pause(&mut self) {
+        if !self.paused_by_us {
+            self.paused_by_us = true;
+            self.send(Action::Pause);
+        }
+    }
+
+    pub fn resume(&mut self) {
+        if self.paused_by_us {
+            self.paused_by_us = false;
+            self.send(Action::Play);
+        }
+    }
+
+    /// 退出时恢复被我们暂停的播放，等后台线程做完再返回，确保进程退出前完成
+    pub fn restore(&mut self) {
+        self.resume();
+        if let Some((tx, handle)) = self.worker.take() {
+            drop(tx);
+            let _ = handle.join();
+        }
+    }
+
+    fn send(&self, action: Action) {
+        if let Some((tx, _)) = &self.worker {
+            let _ = tx.send(action);
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy)]
+enum Action {
+    Play,
+    Pause,
+}
+
+/// 后台线程：暂停时记下当时正在播放的播放器，恢复时只播放它们
+fn work(actions: Receiver<Action>) {
+    let mut paused: Vec<String> = Vec::new();
+    for action in actions {
+        match action {
+            Action::Pause => {
+                for player in playing() {
+                    command(&player, Action::Pause);
+                    paused.push(player);
+                }
+            }
+            Action::Play => {
+                for player in paused.drain(..) {
+                    command(&player, Action::Play);
+                }
+            }
+        }
+    }
+}
+
+#[cfg(any(target_os = "linux", target_os = "macos"))]
+fn quiet(command: &mut Command) -> Option<std::process::Output> {
+    command
+        .stdin(Stdio::null())
+        .stderr(Stdio::null())
+        .output()
+        .ok()
+}
+
+/// 正在播放的 MPRIS 播放器的总线名
+#[cfg(target_os = "linux")]
+fn playing() -> Vec<String> {
+    let Some(names) = quiet(Command::new("dbus-send").args([
+        "--session",
+        "--print-reply",
+        "--dest=org.freedesktop.DBus",
+        "/org/freedesktop/DBus",
+        "org.freedesktop.DBus.ListNames",
+    ])) else {
+        return Vec::new();
+    };
+    let names = String::from_utf8_lossy(&names.stdout);
+    names
+        .split('"')
+        .filter(|name| name.starts_with("org.mpris.MediaPlayer2."))
+        .filter(|player| {
+            // 回复形如 `variant string "Playing"`；暂停或停止的播放器不碰
+            quiet(Command::new("dbus-send").args([
+                "--session",
+                "--print-reply",
+                &format!("--dest={player}"),
+                "/org/mpris/MediaPlayer2",
+                "org.freedesktop.DBus.Properties.Get",
+                "string:org.mpris.MediaPlayer2.Player",
+                "string:PlaybackStatus",
+            ]))
+            .is_some_and(|reply| String::from_utf8_lossy(&reply.stdout).contains("\"Playing\""))
+        })
+        .map(str::to_string)
+        .collect()
+}
+
+#[cfg(target_os = "linux")]
+fn command(player: &str, action: Action) {
+    let method = match action {
+        Action::Play => "org.mpris.MediaPlayer2.Player.Play",
+        Action::Pause => "org.mpris.MediaPlayer2.Player.Pause",
+    };
+    quiet(Command::new("dbus-send").args([
+        "--session",
+        "--type=method_call",
+        &format!("--dest={player}"),
+        "/org/mpris/MediaPlayer2",
+        method,
+    ]));
+}
+
+/// 正在播放的应用名
+#[cfg(target_os = "macos")]
+fn playing() -> Vec<String> {
+    ["Spotify", "Music"]
+        .into_iter()
+        .filter(|app| {
+            let script = format!(
+                "if application \"{app}\" is running then tell application \"{app}\" to get player state as string"
+            );
+            quiet(Command::new("osascript").args(["-e", &script]))
+                .is_some_and(|out| String::from_utf8_lossy(&out.stdout).trim() == "playing")
+        })
+        .map(str::to_string)
+        .collect()
+}
+
+#[cfg(target_os = "macos")]
+fn command(app: &str, action: Action) {
+    let verb = match action {
+        Action::Play => "play",
+        Action::Pause => "pause",
+    };
+    let script =
+        format!("if application \"{app}\" is running then tell application \"{app}\" to {verb}");
+    quiet(Command::new("osascript").args(["-e", &script]));
+}
+
+#[cfg(not(any(target_os = "linux", target_os = "macos")))]
+fn playing() -> Vec<String> {
+    Vec::new()
+}
+
+#[cfg(not(any(target_os = "linux", target_os = "macos")))]
+fn command(_player: &str, _action: Action) {}
@@ -0,0 +1,185 @@
+#[cfg(any(target_os = "linux", target_os = "macos"))]
+use std::process::{Command, Stdio};
+use std::sync::mpsc;
+use std::thread;
+use std::time::Duration;
+
+use crate::Phase;
+use crate::config::PhaseNames;
+
+/// 发送桌面通知（Linux 走 notify-send，macOS 走 osascript），返回是否发送成功；
+/// 会等外部命令结束，界面线程里应放到后台线程调用
+pub fn send(title: &str, body: &str) -> bool {
+    run(title, body)
+}
+
+/// `--live-notify`：一条常驻通知，每分钟更新剩余时间，阶段切换时关闭旧的再显示新的。
+/// 通知服务不支持替换（或不在 Linux 上）时退化为每个阶段一条普通通知。
+pub struct LiveNotification {
+    sender: mpsc::Sender<Message>,
+    worker: thread::JoinHandle<()>,
+    shown: Option<(Phase, u64, bool)>,
+    names: PhaseNames,
+}
+
+enum Message {
+    Show {
+        title: String,
+        body: String,
+        new_phase: bool,
+    },
+    Close,
+}
+
+impl LiveNotification {
+    /// 在后台线程调用外部命令，避免阻塞界面
+    pub fn start(names: PhaseNames) -> Self {
+        let (sender, receiver) = mpsc::channel();
+        let worker = thread::spawn(move || live_worker(receiver));
+        Self {
+            sender,
+            worker,
+            shown: None,
+            names,
+        }
+    }
+
+    /// 每次循环调用；只有阶段、剩余分钟数或运行状态变化时才更新通知
+    pub fn update(&mut self, phase: Phase, remaining: Duration, running: bool) {
+        let minutes = remaining.as_secs().div_ceil(60);
+        let key = (phase, minutes, running);
+        if self.shown == Some(key) {
+            return;
+        }
+        let new_phase = self.shown.is_none_or(|(shown, _, _)| shown != phase);
+        if new_phase && self.shown.is_some() {
+            let _ = self.sender.send(Message::Close);
+        }
+        self.shown = Some(key);
+        let paused = if running { "" } else { " · paused" };
+        let _ = self.sender.send(Message::Show {
+            title: self.names.get(phase).to_string(),
+            body: format!("{minutes} min left{paused}"),
+            new_phase,
+        });
+    }
+
+    /// 退出时关闭通知，并等后台线程处理完
+    pub fn close(self) {
+        let _ = self.sender.send(Message::Close);
+        drop(self.sender);
+        let _ = self.worker.join();
+    }
+}
+
+fn live_worker(receiver: mpsc::Receiver<Message>) {
+    let mut id: Option<u32> = None;
+    let mut replaceable = cfg!(target_os = "linux");
+    for message in receiver {
+        match message {
+            Message::Show {
+                title,
+                body,
+                new_phase,
+            } => {
+                if replaceable {
+                    match show_replacing(id, &title, &body) {
+                        Some(new_id) => id = Some(new_id),
+                        None => replaceable = false,
+                    }
+                }
+                if !replaceable && new_phase {
+                    run(&title, &body);
+                }
+            }
+            Message::Close => {
+                if let Some(id) = id.take() {
+                    close(id);
+                }
+            }
+        }
+    }
+}
+
+/// 显示或替换通知，返回通知 ID；旧版 notify-send 不支持 `--print-id` 时返回 `None`
+#[cfg(target_os = "linux")]
+fn show_replacing(id: Option<u32>, title: &str, body: &str) -> Option<u32> {
+    let mut command = Command::new("notify-send");
+    command.args([
+        "--app-name=pomodoros",
+        "--print-id",
+        "--urgency=low",
+        "--expire-time=0",
+    ]);
+    if let Some(id) = id {
+        command.arg(format!("--replace-id={id}"));
+    }
+    let output = command
+        .args([title, body])
+        .stdin(Stdio::null())
+        .stderr(Stdio::null())
+        .output()
+        .ok()?;
+    if !output.status.success() {
+        return None;
+    }
+    String::from_utf8_lossy(&output.stdout).trim().parse().ok()
+}
+
+#[cfg(not(target_os = "linux"))]
+fn show_replacing(_id: Option<u32>, _title: &str, _body: &str) -> Option<u32> {
+    None
+}
+
+#[cfg(target_os = "linux")]
+fn close(id: u32) {
+    let _ = Command::new("dbus-send")
+        .args([
+            "--session",
+            "--type=method_call",
+            "--dest=org.freedesktop.Notifications",
+            "/org/freedesktop/Notifications",
+            "org.freedesktop.Notifications.CloseNotification",
+            &format!("uint32:{id}"),
+        ])
+        .stdin(Stdio::null())
+        .stdout(Stdio::null())
+        .stderr(Stdio::null())
+        .status();
+}
+
+#[cfg(not(target_os = "linux"))]
+fn close(_id: u32) {}
+
+#[cfg(target_os = "linux")]
+fn run(title: &str, body: &str) -> bool {
+    Command::new("notify-send")
+        .args(["--app-name=pomodoros", title, body])
+        .stdin(Stdio::null())
+        .stdout(Stdio::null())
+        .stderr(Stdio::null())
+        .status()
+        .is_ok_and(|status| status.success())
+}
+
+#[cfg(target_os = "macos")]
+fn run(title: &str, body: &str) -> bool {
+    let quote = |text: &str| text.replace('\\', "\\\\").replace('"', "\\\"");
+    let script = format!(
+        "display notification \"{}\" with title \"{}\"",
+        quote(body),
+        quote(title)
+    );
+    Command::new("osascript")
+        .args(["-e", &script])
+        .stdin(Stdio::null())
+        .stdout(Stdio::null())
+        .stderr(Stdio::null())
+        .status()
+        .is_ok_and(|status| status.success())
+}
+
+#[cfg(not(any(target_os = "linux", target_os = "macos")))]
+fn run(_title: &str, _body: &str) -> bool {
+    false
+}
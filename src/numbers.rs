@@ -0,0 +1,124 @@
+use std::env;
+
+/// 统计输出里数字的千位分隔符：按 `--locale` 或系统的 `LC_ALL` / `LC_NUMERIC` / `LANG` 选择。
+/// 只用于给人看的文本，CSV / JSON 输出不经过这里。
+#[derive(Debug, Clone, Copy)]
+pub struct NumberFormat {
+    separator: char,
+}
+
+impl Default for NumberFormat {
+    fn default() -> Self {
+        Self { separator: ',' }
+    }
+}
+
+impl NumberFormat {
+    pub fn detect(locale: Option<&str>) -> Self {
+        let system = ["LC_ALL", "LC_NUMERIC", "LANG"]
+            .into_iter()
+            .filter_map(|name| env::var(name).ok())
+            .find(|value| !value.is_empty());
+        match locale.map(str::to_string).or(system) {
+            Some(tag) => Self::for_locale(&tag),
+            None => Self::default(),
+        }
+    }
+
+    /// `de_DE.UTF-8`、`fr-CA`、`en` 这样的语言标签
+    pub fn for_locale(tag: &str) -> Self {
+        let tag = tag.split(['.', '@']).next().unwrap_or_default();
+        let mut parts = tag.split(['_', '-']);
+        let language = parts.next().unwrap_or_default().to_ascii_lowercase();
+        let region = parts.next().unwrap_or_default().to_ascii_uppercase();
+        let separator = match (language.as_str(), region.as_str()) {
+            ("de" | "it" | "fr", "CH") | (_, "LI") => '\'',
+            (
+                "de" | "nl" | "es" | "it" | "pt" | "da" | "id" | "tr" | "el" | "ro" | "hr" | "sl"
+                | "sr",
+                _,
+            ) => '.',
+            (
+                "fr" | "ru" | "uk" | "pl" | "cs" | "sk" | "sv" | "fi" | "nb" | "nn" | "no" | "hu"
+                | "bg" | "et" | "lv" | "lt",
+                _,
+            ) => '\u{a0}',
+            _ => ',',
+        };
+        Self { separator }
+    }
+
+    /// 1204 → `1,204`
+    pub fn count(self, value: u64) -> String {
+        let digits = value.to_string();
+        let mut out = String::new();
+        for (index, digit) in digits.chars().enumerate() {
+            if index > 0 && (digits.len() - index).is_multiple_of(3) {
+                out.push(self.separator);
+            }
+            out.push(digit);
+        }
+        out
+    }
+
+    /// 一位小数，小数点随千位分隔符而定：`2.4` 或 `2,4`
+    pub fn decimal(self, value: f64) -> String {
+        let text = format!("{value:.1}");
+        match self.separator {
+            ',' | '\'' => text,
+            _ => text.replace('.', ","),
+        }
+    }
+
+    pub fn plural(self, count: u32, word: &str) -> String {
+        let suffix = if count == 1 { "" } else { "s" };
+        format!("{} {word}{suffix}", self.count(u64::from(count)))
+    }
+
+    /// 与 `timeline::format_secs` 相同的 `2h 15m` 格式，小时数按千位分隔
+    pub fn duration(self, secs: u64) -> String {
+        let minutes = (secs + 30) / 60;
+        if minutes >= 60 {
+            format!("{}h {:02}m", self.count(minutes / 60), minutes % 60)
+        } else {
+            format!("{minutes}m")
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn counts_are_grouped_by_locale() {
+        assert_eq!(NumberFormat::for_locale("en").count(1204), "1,204");
+        assert_eq!(NumberFormat::for_locale("de_DE.UTF-8").count(1204), "1.204");
+        assert_eq!(NumberFormat::for_locale("fr-CA").count(1204), "1\u{a0}204");
+        assert_eq!(NumberFormat::for_locale("de_CH").count(1204), "1'204");
+        assert_eq!(
+            NumberFormat::for_locale("en_US").count(1_234_567),
+            "1,234,567"
+        );
+    }
+
+    #[test]
+    fn values_below_a_thousand_have_no_separator() {
+        for tag in ["en", "de_DE.UTF-8", "fr-CA", "de_CH"] {
+            let numbers = NumberFormat::for_locale(tag);
+            assert_eq!(numbers.count(0), "0", "{tag}");
+            assert_eq!(numbers.count(999), "999", "{tag}");
+            assert_eq!(numbers.plural(1, "session"), "1 session", "{tag}");
+            assert_eq!(numbers.duration(59 * 60), "59m", "{tag}");
+        }
+    }
+
+    #[test]
+    fn the_decimal_mark_follows_the_locale() {
+        assert_eq!(NumberFormat::for_locale("en").decimal(2.44), "2.4");
+        assert_eq!(NumberFormat::for_locale("de_DE.UTF-8").decimal(2.44), "2,4");
+        assert_eq!(NumberFormat::for_locale("fr-CA").decimal(2.44), "2,4");
+        // 瑞士用撇号分组，小数点仍是点
+        assert_eq!(NumberFormat::for_locale("de_CH").decimal(2.44), "2.4");
+    }
+}
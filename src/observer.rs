@@ -0,0 +1,32 @@
+use std::fmt::Debug;
+use std::time::Duration;
+
+use crate::Phase;
+use crate::history::SessionRecord;
+
+/// 计时器事件的回调，通过 `PomodoroApp::observe` 注册，可注册多个。
+///
+/// 所有回调都在调用 `update` 的线程里同步执行，按注册顺序依次调用，因此不能阻塞；
+/// 需要调用外部命令或访问网络的实现应自行交给后台线程。
+///
+/// 同一次 `update` 内的顺序固定为：`on_pause` / `on_resume` → `on_phase_start`
+/// → `on_tick` 或 `on_time_up` → `on_phase_complete`；下一阶段的 `on_phase_start`
+/// 在它开始计时的那次 `update` 中触发。
+pub trait PomodoroObserver: Debug {
+    /// 阶段第一次开始计时（重置后再次开始也算）
+    fn on_phase_start(&mut self, _phase: Phase, _total: Duration) {}
+
+    /// 计时中每次 `update` 之后的剩余时间；超时模式下为零
+    fn on_tick(&mut self, _phase: Phase, _remaining: Duration) {}
+
+    /// 剩余时间归零；`--overrun` 下此时阶段尚未结束
+    fn on_time_up(&mut self, _phase: Phase) {}
+
+    /// 阶段结束并写入历史记录（跳过的阶段不会触发）
+    fn on_phase_complete(&mut self, _record: &SessionRecord) {}
+
+    /// 由手动或自动暂停引起的停止计时
+    fn on_pause(&mut self, _phase: Phase, _remaining: Duration) {}
+
+    fn on_resume(&mut self, _phase: Phase, _remaining: Duration) {}
+}
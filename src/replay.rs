@@ -0,0 +1,85 @@
+use std::fmt::Write as _;
+use std::path::Path;
+
+use anyhow::Result;
+use chrono::NaiveDate;
+
+use crate::Phase;
+use crate::config::PhaseNames;
+use crate::history::{self, SessionRecord};
+use crate::numbers::NumberFormat;
+use crate::timeline::format_secs;
+
+pub fn run(path: &Path, date: NaiveDate, names: &PhaseNames, numbers: NumberFormat) -> Result<()> {
+    let records = history::load(path)?;
+    print!("{}", render(&records, date, names, numbers));
+    Ok(())
+}
+
+/// 按时间顺序列出某天的每个阶段及其标签、备注；跨午夜的阶段两天都会列出
+pub fn render(
+    records: &[SessionRecord],
+    date: NaiveDate,
+    names: &PhaseNames,
+    numbers: NumberFormat,
+) -> String {
+    let mut sessions: Vec<&SessionRecord> = records
+        .iter()
+        .filter(|r| r.start.date() <= date && r.end.date() >= date)
+        .collect();
+    sessions.sort_by_key(|r| r.start);
+
+    let mut out = String::new();
+    let _ = writeln!(out, "Sessions on {date}");
+    if sessions.is_empty() {
+        let _ = writeln!(out, "No sessions recorded on this day.");
+        return out;
+    }
+
+    let mut focus_secs = 0;
+    let mut break_secs = 0;
+    let mut focus_count = 0;
+    for record in &sessions {
+        let mut details = Vec::new();
+        if !record.completed {
+            details.push("(partial)".to_string());
+        }
+        if let Some(batch) = &record.batch {
+            details.push(format!("[{batch}]"));
+        }
+        if let Some(note) = &record.note {
+            details.push(note.clone());
+        }
+        if record.interruptions > 0 {
+            details.push(format!("{}× paused", record.interruptions));
+        }
+        if record.start.date() != record.end.date() {
+            details.push(format!("({} → {})", record.start.date(), record.end.date()));
+        }
+        let line = format!(
+            "{}–{}  {:<11} {:>7}  {}",
+            record.start.format("%H:%M"),
+            record.end.format("%H:%M"),
+            names.get(record.phase),
+            format_secs(record.duration_secs),
+            details.join("  ")
+        );
+        let _ = writeln!(out, "{}", line.trim_end());
+
+        if record.phase == Phase::Focus {
+            focus_secs += record.duration_secs;
+            focus_count += u32::from(record.completed);
+        } else {
+            break_secs += record.duration_secs;
+        }
+    }
+
+    let _ = writeln!(
+        out,
+        "Total: focus {} in {}  ·  breaks {}",
+        format_secs(focus_secs),
+        numbers.plural(focus_count, "session"),
+        format_secs(break_secs)
+    );
+    out
+}
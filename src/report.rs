@@ -0,0 +1,130 @@
+use std::fmt::Write as _;
+use std::fs;
+use std::path::Path;
+
+use anyhow::{Context, Result};
+use chrono::{Local, NaiveDate, TimeDelta};
+
+use crate::Phase;
+use crate::history::{self, SessionRecord};
+use crate::numbers::NumberFormat;
+use crate::timeline::format_secs;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, clap::ValueEnum)]
+pub enum ReportFormat {
+    /// Markdown
+    Md,
+}
+
+/// `today`、`yesterday` 或 `YYYY-MM-DD`
+pub fn parse_day(text: &str) -> Result<NaiveDate, String> {
+    let today = Local::now().date_naive();
+    match text {
+        "today" => Ok(today),
+        "yesterday" => Ok(today - TimeDelta::days(1)),
+        _ => NaiveDate::parse_from_str(text, "%Y-%m-%d")
+            .map_err(|_| format!("expected today, yesterday or YYYY-MM-DD, got `{text}`")),
+    }
+}
+
+pub fn run(
+    path: &Path,
+    date: NaiveDate,
+    format: ReportFormat,
+    out: Option<&Path>,
+    numbers: NumberFormat,
+) -> Result<()> {
+    let records = history::load(path)?;
+    let text = match format {
+        ReportFormat::Md => markdown(&records, date, numbers),
+    };
+    match out {
+        Some(out) => {
+            fs::write(out, text).with_context(|| format!("failed to write {}", out.display()))
+        }
+        None => {
+            print!("{text}");
+            Ok(())
+        }
+    }
+}
+
+/// 某天的专注记录：每个会话一条，按批次标签分组，末尾是合计
+pub fn markdown(records: &[SessionRecord], date: NaiveDate, numbers: NumberFormat) -> String {
+    let mut sessions: Vec<&SessionRecord> = records
+        .iter()
+        .filter(|r| r.phase == Phase::Focus && r.end.date() == date)
+        .collect();
+    sessions.sort_by_key(|r| r.start);
+
+    let mut out = String::new();
+    let _ = writeln!(out, "# Focus report for {date}");
+    let _ = writeln!(out);
+    if sessions.is_empty() {
+        let _ = writeln!(out, "No sessions.");
+        return out;
+    }
+
+    // 按首次出现的顺序分组，没有标签的会话放在最后
+    let mut groups: Vec<(Option<&str>, Vec<&SessionRecord>)> = Vec::new();
+    for record in &sessions {
+        let label = record.batch.as_deref();
+        match groups.iter_mut().find(|(l, _)| *l == label) {
+            Some((_, items)) => items.push(record),
+            None => groups.push((label, vec![record])),
+        }
+    }
+    groups.sort_by_key(|(label, _)| label.is_none());
+    let labelled = groups.iter().any(|(label, _)| label.is_some());
+
+    for (label, items) in &groups {
+        if labelled {
+            let focus: u64 = items.iter().map(|r| r.duration_secs).sum();
+            let _ = writeln!(
+                out,
+                "## {} ({})",
+                label.unwrap_or("Other"),
+                format_secs(focus)
+            );
+            let _ = writeln!(out);
+        }
+        for record in items {
+            let _ = write!(
+                out,
+                "- {}–{} · {}",
+                record.start.format("%H:%M"),
+                record.end.format("%H:%M"),
+                format_secs(record.duration_secs)
+            );
+            if !record.completed {
+                let _ = write!(out, " · *partial*");
+            }
+            if let Some(note) = &record.note {
+                let _ = write!(out, " · {note}");
+            }
+            if record.interruptions > 0 {
+                let _ = write!(
+                    out,
+                    " · {}",
+                    numbers.plural(record.interruptions, "interruption")
+                );
+            }
+            let _ = writeln!(out);
+        }
+        let _ = writeln!(out);
+    }
+
+    let focus: u64 = sessions.iter().map(|r| r.duration_secs).sum();
+    let interruptions: u32 = sessions.iter().map(|r| r.interruptions).sum();
+    let _ = writeln!(
+        out,
+        "**Total:** {} · {} focus · {}",
+        numbers.plural(
+            sessions.iter().filter(|r| r.completed).count() as u32,
+            "session"
+        ),
+        numbers.duration(focus),
+        numbers.plural(interruptions, "interruption")
+    );
+    out
+}
@@ -0,0 +1,76 @@
+use std::f64::consts::TAU;
+
+use ratatui::buffer::Buffer;
+use ratatui::layout::Rect;
+use ratatui::style::Color;
+use ratatui::symbols::Marker;
+use ratatui::widgets::Widget;
+use ratatui::widgets::canvas::{Canvas, Points};
+
+/// 画得下圆环的最小区域，再小就退回进度条
+const MIN_WIDTH: u16 = 20;
+const MIN_HEIGHT: u16 = 9;
+
+/// 围绕大号计时器的圆环进度，从顶部顺时针填充
+#[derive(Debug, Clone)]
+pub struct Ring {
+    ratio: f64,
+    color: Color,
+    /// 未走完部分的颜色
+    track: Color,
+}
+
+impl Ring {
+    pub fn new(ratio: f64, color: Color, track: Color) -> Self {
+        Self {
+            ratio,
+            color,
+            track,
+        }
+    }
+
+    pub fn fits(area: Rect) -> bool {
+        area.width >= MIN_WIDTH && area.height >= MIN_HEIGHT
+    }
+}
+
+impl Widget for Ring {
+    fn render(self, area: Rect, buf: &mut Buffer) {
+        if !Ring::fits(area) {
+            return;
+        }
+        let ratio = if self.ratio.is_finite() {
+            self.ratio.clamp(0.0, 1.0)
+        } else {
+            0.0
+        };
+        // 字符格的高约为宽的两倍：以半行为单位，圆才不会被压扁
+        let width = f64::from(area.width);
+        let height = f64::from(area.height) * 2.0;
+        let radius = width.min(height) / 2.0 - 1.0;
+        // 盲文点阵每格横向 2 个点，按周长取足够密的采样
+        let steps = (TAU * radius * 2.0).ceil() as usize;
+        let filled = (steps as f64 * ratio).round() as usize;
+        let point = |step: usize| {
+            let angle = TAU * step as f64 / steps as f64;
+            (radius * angle.sin(), radius * angle.cos())
+        };
+        let done: Vec<(f64, f64)> = (0..filled).map(point).collect();
+        let rest: Vec<(f64, f64)> = (filled..steps).map(point).collect();
+        Canvas::default()
+            .marker(Marker::Braille)
+            .x_bounds([-width / 2.0, width / 2.0])
+            .y_bounds([-height / 2.0, height / 2.0])
+            .paint(|ctx| {
+                ctx.draw(&Points {
+                    coords: &rest,
+                    color: self.track,
+                });
+                ctx.draw(&Points {
+                    coords: &done,
+                    color: self.color,
+                });
+            })
+            .render(area, buf);
+    }
+}
@@ -0,0 +1,96 @@
+use std::fs;
+use std::path::Path;
+
+use anyhow::{Context, Result, bail};
+use base64::Engine;
+use base64::engine::general_purpose::URL_SAFE_NO_PAD;
+use serde::{Deserialize, Serialize};
+
+use crate::config;
+use crate::state::{self, SavedState};
+
+/// 格式变化时递增，导入时拒绝看不懂的版本
+const VERSION: u32 = 1;
+
+/// `--export-state` 导出的内容：配置文件原文加计时状态，紧凑 JSON 再 base64（URL 安全、无填充）
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct Bundle {
+    version: u32,
+    /// 配置文件原文；没有配置文件时为空
+    config: Option<String>,
+    state: Option<SavedState>,
+}
+
+/// 读取配置文件和状态文件，编码成一行字符串
+pub fn export(config_path: Option<&Path>, state_path: &Path) -> Result<String> {
+    let config = match config_path.filter(|path| path.exists()) {
+        Some(path) => Some(
+            fs::read_to_string(path)
+                .with_context(|| format!("failed to read config file {}", path.display()))?,
+        ),
+        None => None,
+    };
+    let bundle = Bundle {
+        version: VERSION,
+        config,
+        state: state::load(state_path)?,
+    };
+    Ok(URL_SAFE_NO_PAD.encode(serde_json::to_vec(&bundle)?))
+}
+
+/// 解码并校验后写回配置文件和状态文件；原有配置文件内容不同时先备份为 `.bak`
+pub fn import(text: &str, config_path: Option<&Path>, state_path: &Path) -> Result<()> {
+    let bundle = decode(text)?;
+    if let Some(text) = &bundle.config {
+        let Some(path) = config_path else {
+            bail!("cannot determine the config directory; pass --config");
+        };
+        if let Ok(old) = fs::read_to_string(path)
+            && old != *text
+        {
+            let backup = path.with_extension("toml.bak");
+            fs::write(&backup, old)
+                .with_context(|| format!("failed to write {}", backup.display()))?;
+            println!("Backed up the old config to {}.", backup.display());
+        }
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent)
+                .with_context(|| format!("failed to create {}", parent.display()))?;
+        }
+        fs::write(path, text).with_context(|| format!("failed to write {}", path.display()))?;
+        println!("Imported the config into {}.", path.display());
+    }
+    if let Some(saved) = &bundle.state {
+        state::save(state_path, saved)?;
+        println!(
+            "Imported the counters ({} today, streak {}) into {}.",
+            saved.day_completed,
+            saved.streak,
+            state_path.display()
+        );
+    }
+    if bundle.config.is_none() && bundle.state.is_none() {
+        println!("The state string was empty; nothing was imported.");
+    }
+    Ok(())
+}
+
+/// 任何一步失败都只报错，不写文件
+fn decode(text: &str) -> Result<Bundle> {
+    let bytes = URL_SAFE_NO_PAD
+        .decode(text.trim())
+        .context("not a state string from --export-state (invalid base64)")?;
+    let bundle: Bundle = serde_json::from_slice(&bytes)
+        .context("not a state string from --export-state (malformed payload)")?;
+    if bundle.version != VERSION {
+        bail!(
+            "state string version {} is not supported (expected {VERSION})",
+            bundle.version
+        );
+    }
+    if let Some(text) = &bundle.config {
+        config::parse(text).context("the state string contains an invalid config")?;
+    }
+    Ok(bundle)
+}
@@ -0,0 +1,193 @@
+use std::time::{Duration, Instant};
+
+use anyhow::{Result, bail, ensure};
+use chrono::Local;
+
+use crate::clock::ManualClock;
+use crate::history::SessionRecord;
+use crate::{Phase, PomodoroApp, PomodoroConfig};
+
+/// 用手动时钟快进驱动状态机，检查阶段切换与计数是否始终正确
+pub fn run(config: PomodoroConfig, hours: u64, step: Duration) -> Result<()> {
+    ensure!(!step.is_zero(), "--step-ms must be greater than 0");
+    let Some(span) = hours.checked_mul(3600).map(Duration::from_secs) else {
+        bail!("simulation too long: {hours} hours");
+    };
+    let started = Instant::now();
+    let records = drive(config, span, step)?;
+    let count = |phase| records.iter().filter(|r| r.phase == phase).count();
+    println!(
+        "Simulated {hours}h in {:.2?}: {} focus sessions, {} short breaks, {} long breaks; all checks passed.",
+        started.elapsed(),
+        count(Phase::Focus),
+        count(Phase::ShortBreak),
+        count(Phase::LongBreak),
+    );
+    Ok(())
+}
+
+/// 模拟 `span` 这么久，返回产生的历史记录（每个完成的阶段一条，按时间顺序）
+fn drive(mut config: PomodoroConfig, span: Duration, step: Duration) -> Result<Vec<SessionRecord>> {
+    config.mute = true;
+    config.animations = false;
+    // 这两种模式需要手动操作，无法自动推进
+    config.overrun = false;
+    config.alert_until_ack = false;
+    config.pause_after_long = false;
+    config.warmup = Duration::ZERO;
+    config.auto_quit_idle = Duration::ZERO;
+    let (long_every, first_long) = (config.long_every, config.first_break_long);
+
+    let clock = ManualClock::new(Local::now().naive_local());
+    let mut app = PomodoroApp::with_clock(config, Box::new(clock.clone()));
+    app.toggle();
+
+    let mut simulated = Duration::ZERO;
+    let mut counts = [0u64; 3];
+    let mut records = Vec::new();
+    while simulated < span {
+        clock.advance(step);
+        simulated += step;
+
+        let before = (app.phase, app.completed_focus);
+        app.update();
+        ensure!(
+            app.remaining <= app.total,
+            "remaining {:?} exceeds total {:?} at {:?}",
+            app.remaining,
+            app.total,
+            simulated
+        );
+        ensure!(
+            app.running || app.ready_until.is_some(),
+            "timer stopped on its own at {simulated:?}"
+        );
+
+        for record in app.pending_records.drain(..) {
+            ensure!(
+                record.phase == before.0,
+                "logged {:?} while in {:?}",
+                record.phase,
+                before.0
+            );
+            ensure!(record.start <= record.end, "record ends before it starts");
+            records.push(record);
+        }
+        if app.phase == before.0 {
+            ensure!(
+                app.completed_focus == before.1,
+                "session count changed without a phase change"
+            );
+            continue;
+        }
+
+        // 阶段切换：专注后按节奏进入长/短休息，休息后回到专注。
+        // 期望值单独按规则重新算，不调用 `long_break_due`
+        let done = before.1 + 1;
+        let expected = match before.0 {
+            Phase::Focus if done.is_multiple_of(long_every) || (first_long && done == 1) => {
+                Phase::LongBreak
+            }
+            Phase::Focus => Phase::ShortBreak,
+            Phase::ShortBreak | Phase::LongBreak => Phase::Focus,
+        };
+        ensure!(
+            app.phase == expected,
+            "{:?} was followed by {:?} (expected {:?}) after {} sessions",
+            before.0,
+            app.phase,
+            expected,
+            before.1
+        );
+        let finished_focus = u32::from(before.0 == Phase::Focus);
+        ensure!(
+            app.completed_focus == before.1 + finished_focus,
+            "session count went from {} to {}",
+            before.1,
+            app.completed_focus
+        );
+        counts[before.0 as usize] += 1;
+    }
+
+    ensure!(
+        records.len() as u64 == counts.iter().sum::<u64>(),
+        "{} history records for {} finished phases",
+        records.len(),
+        counts.iter().sum::<u64>()
+    );
+    Ok(records)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::history;
+    use crate::tests::test_config;
+
+    /// 模拟 `hours` 小时，把产生的记录写进历史文件再读回来逐行检查
+    fn check_history(hours: u64, step: Duration) {
+        let config = test_config(&[
+            "--focus-seconds",
+            "1500",
+            "--short-seconds",
+            "300",
+            "--long-seconds",
+            "900",
+            "-e",
+            "4",
+        ]);
+        let records = drive(config, Duration::from_secs(hours * 3600), step).unwrap();
+
+        let dir =
+            std::env::temp_dir().join(format!("pomodoros-simulate-{}-{hours}", std::process::id()));
+        let path = dir.join("history.csv");
+        for record in &records {
+            history::append(&path, record).unwrap();
+        }
+        let rows = history::load(&path).unwrap();
+        std::fs::remove_dir_all(&dir).unwrap();
+
+        assert_eq!(rows.len(), records.len());
+        // 25 + 5 分钟一轮，第四个专注后换成 15 分钟的长休息
+        let mut focus = 0u32;
+        for (index, row) in rows.iter().enumerate() {
+            let expected = if index % 2 == 0 {
+                focus += 1;
+                (Phase::Focus, 1500)
+            } else if focus.is_multiple_of(4) {
+                (Phase::LongBreak, 900)
+            } else {
+                (Phase::ShortBreak, 300)
+            };
+            assert_eq!((row.phase, row.duration_secs), expected, "row {index}");
+            assert!(row.completed, "row {index}");
+            // 开始和结束都落在某次刷新上，文件里的时间又只精确到秒，
+            // 墙上时间与计时最多差一个步长加一秒
+            let wall = (row.end - row.start).num_milliseconds() - expected.1 as i64 * 1000;
+            assert!(
+                wall.unsigned_abs() <= step.as_millis() as u64 + 1000,
+                "row {index} spans {wall}ms more than its duration"
+            );
+            if let Some(next) = rows.get(index + 1) {
+                assert!(next.start >= row.end, "row {index} overlaps the next one");
+            }
+        }
+        // 每 4 个专注一轮 130 分钟
+        assert!(
+            u64::from(focus) >= hours * 60 / 130 * 4,
+            "only {focus} focus sessions"
+        );
+    }
+
+    #[test]
+    fn simulated_history_rows_follow_the_cycle() {
+        check_history(6, Duration::from_millis(250));
+    }
+
+    /// 一周的量，`cargo test -- --ignored` 时运行
+    #[test]
+    #[ignore]
+    fn simulated_week_of_history() {
+        check_history(7 * 24, Duration::from_millis(700));
+    }
+}
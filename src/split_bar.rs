@@ -0,0 +1,90 @@
+use ratatui::buffer::Buffer;
+use ratatui::layout::Rect;
+use ratatui::style::{Color, Modifier, Style};
+use ratatui::widgets::Widget;
+
+use crate::theme::Theme;
+
+/// 左侧为已用时间、右侧为剩余时间的进度条，中间有分隔线，下方标注两侧时长
+#[derive(Debug, Clone)]
+pub struct SplitBar<'a> {
+    ratio: f64,
+    elapsed: &'a str,
+    remaining: &'a str,
+    color: Color,
+    theme: &'a Theme,
+}
+
+impl<'a> SplitBar<'a> {
+    pub fn new(
+        ratio: f64,
+        elapsed: &'a str,
+        remaining: &'a str,
+        color: Color,
+        theme: &'a Theme,
+    ) -> Self {
+        Self {
+            ratio,
+            elapsed,
+            remaining,
+            color,
+            theme,
+        }
+    }
+}
+
+impl Widget for SplitBar<'_> {
+    fn render(self, area: Rect, buf: &mut Buffer) {
+        if area.width == 0 || area.height == 0 {
+            return;
+        }
+        let ratio = if self.ratio.is_finite() {
+            self.ratio.clamp(0.0, 1.0)
+        } else {
+            0.0
+        };
+        // 分隔线所在列，满格时落在最后一列
+        let filled = ((f64::from(area.width) * ratio).round() as u16).min(area.width);
+        let divider = filled.min(area.width - 1);
+        for x in 0..area.width {
+            let cell = buf.get_mut(area.x + x, area.y);
+            if x == divider {
+                cell.set_symbol("┃").set_style(
+                    Style::default()
+                        .fg(self.theme.text)
+                        .add_modifier(Modifier::BOLD),
+                );
+            } else if x < filled {
+                cell.set_symbol(" ")
+                    .set_style(Style::default().bg(self.color));
+            } else {
+                cell.set_symbol("░")
+                    .set_style(Style::default().fg(self.theme.faint));
+            }
+        }
+
+        if area.height < 2 {
+            return;
+        }
+        let label_y = area.y + 1;
+        let left = format!("{} elapsed", self.elapsed);
+        let right = format!("{} left", self.remaining);
+        buf.set_stringn(
+            area.x,
+            label_y,
+            &left,
+            usize::from(area.width),
+            Style::default().fg(self.color),
+        );
+        let right_width = right.chars().count();
+        // 两侧标签放不下时只保留左侧
+        if right_width + left.chars().count() < usize::from(area.width) {
+            buf.set_string(
+                area.x + area.width - right_width as u16,
+                label_y,
+                &right,
+                Style::default().fg(self.theme.muted),
+            );
+        }
+    }
+}
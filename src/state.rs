@@ -0,0 +1,133 @@
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+use anyhow::{Context, Result};
+use chrono::{Local, NaiveDate};
+use serde::{Deserialize, Serialize};
+
+use crate::Phase;
+
+/// 退出时保存、启动时恢复的计时状态
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SavedState {
+    pub phase: Phase,
+    pub total_secs: u64,
+    pub remaining_secs: u64,
+    /// 毫秒精度的剩余时间，避免多次重启累积误差；旧文件没有此字段
+    #[serde(default)]
+    pub remaining_ms: Option<u64>,
+    pub completed_focus: u32,
+    /// 每日计数所属的日期
+    pub day: NaiveDate,
+    pub day_completed: u32,
+    /// 连续有完成专注的天数
+    pub streak: u32,
+    /// `reset-debt` 的日期，此前的欠账不再计算
+    #[serde(default)]
+    pub debt_reset: Option<NaiveDate>,
+    /// 当天专注中途暂停或跳过的次数
+    #[serde(default)]
+    pub day_interruptions: u32,
+    /// 当前阶段退出时已作为未完成记录写入历史的秒数，恢复后完成时不再重复计入
+    #[serde(default)]
+    pub logged_secs: u64,
+}
+
+impl Default for SavedState {
+    fn default() -> Self {
+        Self {
+            phase: Phase::Focus,
+            total_secs: 0,
+            remaining_secs: 0,
+            remaining_ms: None,
+            completed_focus: 0,
+            day: Local::now().date_naive(),
+            day_completed: 0,
+            streak: 0,
+            debt_reset: None,
+            day_interruptions: 0,
+            logged_secs: 0,
+        }
+    }
+}
+
+impl SavedState {
+    /// 保存时的剩余时间（不超过总时长）
+    pub fn remaining(&self) -> Duration {
+        let remaining = match self.remaining_ms {
+            Some(ms) => Duration::from_millis(ms),
+            None => Duration::from_secs(self.remaining_secs),
+        };
+        remaining.min(Duration::from_secs(self.total_secs))
+    }
+
+    /// 按 `today` 折算后的连续天数（中断超过一天即为 0）
+    pub fn current_streak(&self, today: NaiveDate) -> u32 {
+        let alive =
+            self.day == today || (self.day_completed > 0 && self.day.succ_opt() == Some(today));
+        if alive { self.streak } else { 0 }
+    }
+}
+
+/// Default location: `<data dir>/pomodoros/state.json`.
+pub fn default_path() -> Option<PathBuf> {
+    dirs::data_dir().map(|dir| dir.join("pomodoros").join("state.json"))
+}
+
+/// A missing state file means a first run.
+pub fn load(path: &Path) -> Result<Option<SavedState>> {
+    if !path.exists() {
+        return Ok(None);
+    }
+    let text = fs::read_to_string(path)
+        .with_context(|| format!("failed to read state file {}", path.display()))?;
+    let state = serde_json::from_str(&text)
+        .with_context(|| format!("invalid state file {}", path.display()))?;
+    Ok(Some(state))
+}
+
+pub fn save(path: &Path, state: &SavedState) -> Result<()> {
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent)
+            .with_context(|| format!("failed to create {}", parent.display()))?;
+    }
+    let text = serde_json::to_string_pretty(state)?;
+    // 先写临时文件再重命名，避免中途退出留下半个文件
+    let tmp = path.with_extension("json.tmp");
+    fs::write(&tmp, text).with_context(|| format!("failed to write {}", tmp.display()))?;
+    fs::rename(&tmp, path).with_context(|| format!("failed to write {}", path.display()))?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn remaining_survives_save_and_load_to_the_millisecond() {
+        let dir = std::env::temp_dir().join(format!("pomodoros-state-{}", std::process::id()));
+        let path = dir.join("state.json");
+        let remaining = Duration::from_millis(12_734);
+        let state = SavedState {
+            total_secs: 25 * 60,
+            remaining_secs: remaining.as_secs(),
+            remaining_ms: Some(remaining.as_millis() as u64),
+            ..SavedState::default()
+        };
+        save(&path, &state).unwrap();
+        let loaded = load(&path).unwrap().unwrap();
+        assert_eq!(loaded.remaining(), remaining);
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn old_state_files_fall_back_to_whole_seconds() {
+        let state: SavedState = serde_json::from_str(
+            r#"{"phase":"focus","total_secs":1500,"remaining_secs":12,
+                "completed_focus":0,"day":"2024-06-01","day_completed":0,"streak":0}"#,
+        )
+        .unwrap();
+        assert_eq!(state.remaining(), Duration::from_secs(12));
+    }
+}
@@ -0,0 +1,511 @@
+use std::cmp::Ordering;
+use std::collections::BTreeMap;
+use std::fmt::Write as _;
+use std::path::Path;
+
+use anyhow::Result;
+use chrono::{Datelike, NaiveDate, TimeDelta, Weekday};
+
+use crate::Phase;
+use crate::history::{self, SessionRecord};
+use crate::numbers::NumberFormat;
+use crate::state::SavedState;
+
+/// `stats` 的滚动平均覆盖的天数
+const AVERAGE_DAYS: u32 = 28;
+
+/// 每日专注统计
+#[derive(Debug, Default, Clone, Copy)]
+pub struct DayTotals {
+    pub sessions: u32,
+    pub focus_secs: u64,
+}
+
+/// 按结束时间所在的本地日期汇总专注阶段；未完成的阶段只计时长，不计次数
+pub fn focus_by_day(records: &[SessionRecord]) -> BTreeMap<NaiveDate, DayTotals> {
+    let mut days: BTreeMap<NaiveDate, DayTotals> = BTreeMap::new();
+    for record in records.iter().filter(|r| r.phase == Phase::Focus) {
+        let day = days.entry(record.end.date()).or_default();
+        day.sessions += u32::from(record.completed);
+        day.focus_secs += record.duration_secs;
+    }
+    days
+}
+
+/// `day` 所在周的周一
+pub fn week_start(day: NaiveDate) -> NaiveDate {
+    day - TimeDelta::days(i64::from(day.weekday().num_days_from_monday()))
+}
+
+/// `day` 所在周（周一到周日）完成的专注次数
+pub fn week_sessions(records: &[SessionRecord], day: NaiveDate) -> u32 {
+    let start = week_start(day);
+    focus_by_day(records)
+        .range(start..start + TimeDelta::days(7))
+        .map(|(_, totals)| totals.sessions)
+        .sum()
+}
+
+/// 最近七天每天是否达到当天的目标：● 达到，○ 没达到（今天还没结束，也算没达到），
+/// · 没有数据（那天没有运行计时器，或还没有任何目标）。
+///
+/// 目标按 `goals.csv` 里那天记下的为准；有记录但没记目标的日子（比如早于目标文件的历史）
+/// 用之前最近一次记下的目标，再没有就用现在的目标。
+pub fn goal_week(
+    days: &BTreeMap<NaiveDate, DayTotals>,
+    goals: &BTreeMap<NaiveDate, u32>,
+    goal: Option<u32>,
+    today: NaiveDate,
+    ascii: bool,
+) -> Option<String> {
+    if goal.is_none() && goals.is_empty() {
+        return None;
+    }
+    let (met_mark, missed_mark, none_mark) = if ascii {
+        ("*", "o", ".")
+    } else {
+        ("●", "○", "·")
+    };
+    let first = today - TimeDelta::days(6);
+    let mut marks = Vec::new();
+    let (mut met, mut counted) = (0, 0);
+    for offset in 0..7 {
+        let day = first + TimeDelta::days(offset);
+        let sessions = days.get(&day).map(|d| d.sessions);
+        let in_effect = match goals.get(&day) {
+            Some(&goal) => Some(goal),
+            None if sessions.is_some() => goals.range(..day).next_back().map(|(_, &g)| g).or(goal),
+            None => None,
+        };
+        marks.push(match in_effect {
+            None => none_mark,
+            Some(goal) => {
+                counted += 1;
+                if sessions.unwrap_or(0) >= goal {
+                    met += 1;
+                    met_mark
+                } else {
+                    missed_mark
+                }
+            }
+        });
+    }
+    let dash = if ascii { "-" } else { "–" };
+    Some(format!(
+        "{}  {met} of {counted} met ({}{dash}{}, {none_mark} = no data)",
+        marks.join(" "),
+        first.format("%a"),
+        today.format("%a"),
+    ))
+}
+
+/// Rules for carrying a daily-goal shortfall forward.
+#[derive(Debug, Clone, Copy)]
+pub struct DebtRules {
+    pub goal: u32,
+    /// 欠账上限
+    pub cap: u32,
+    /// 周末不产生新的欠账（但周末完成的专注仍可抵扣）
+    pub skip_weekends: bool,
+    /// 只统计最近 N 天
+    pub window_days: u32,
+}
+
+/// 截至今天之前累计的欠账：每天 `goal - completed`，多完成的部分抵扣，结果限制在 `0..=cap`。
+/// 从最早的历史记录、`reset` 日期和窗口起点中最晚的一天开始计算；今天尚未结束，不计入。
+pub fn carried_debt(
+    days: &BTreeMap<NaiveDate, DayTotals>,
+    rules: DebtRules,
+    reset: Option<NaiveDate>,
+    today: NaiveDate,
+) -> u32 {
+    let Some(first) = days.keys().next().copied() else {
+        return 0;
+    };
+    let window_start = today - TimeDelta::days(i64::from(rules.window_days));
+    let mut day = [Some(first), reset, Some(window_start)]
+        .into_iter()
+        .flatten()
+        .max()
+        .unwrap_or(first);
+
+    let mut debt: i64 = 0;
+    while day < today {
+        let weekend = matches!(day.weekday(), Weekday::Sat | Weekday::Sun);
+        let goal = if rules.skip_weekends && weekend {
+            0
+        } else {
+            rules.goal
+        };
+        let done = days.get(&day).map_or(0, |d| d.sessions);
+        debt = (debt + i64::from(goal) - i64::from(done)).clamp(0, i64::from(rules.cap));
+        day = day.succ_opt().unwrap_or(today);
+    }
+    debt as u32
+}
+
+/// 最近若干天的日均专注
+#[derive(Debug, Clone, Copy)]
+pub struct Average {
+    pub sessions: f64,
+    pub focus_secs: f64,
+}
+
+/// 今天之前 `window` 天的日均值，没有记录的日子（包括第一条记录之前的日子）按零计；
+/// 今天之前没有任何记录时为 `None`。
+pub fn rolling_average(
+    days: &BTreeMap<NaiveDate, DayTotals>,
+    today: NaiveDate,
+    window: u32,
+) -> Option<Average> {
+    days.keys().next().filter(|first| **first < today)?;
+    let start = today - TimeDelta::days(i64::from(window));
+    let span = f64::from(window.max(1));
+    let (sessions, focus_secs) = days
+        .range(start..today)
+        .fold((0u64, 0u64), |(sessions, secs), (_, d)| {
+            (sessions + u64::from(d.sessions), secs + d.focus_secs)
+        });
+    Some(Average {
+        sessions: sessions as f64 / span,
+        focus_secs: focus_secs as f64 / span,
+    })
+}
+
+/// 一天的回顾
+#[derive(Debug, Clone, Copy)]
+pub struct DaySummary {
+    pub date: NaiveDate,
+    pub totals: DayTotals,
+    pub break_secs: u64,
+    /// 只有当天的运行状态里有记录，更早的日期为 `None`
+    pub interruptions: Option<u32>,
+    pub goal: Option<u32>,
+    pub numbers: NumberFormat,
+}
+
+pub fn day_summary(
+    records: &[SessionRecord],
+    date: NaiveDate,
+    interruptions: Option<u32>,
+    goal: Option<u32>,
+    numbers: NumberFormat,
+) -> DaySummary {
+    DaySummary {
+        date,
+        totals: focus_by_day(records)
+            .get(&date)
+            .copied()
+            .unwrap_or_default(),
+        break_secs: records
+            .iter()
+            .filter(|r| r.phase != Phase::Focus && r.end.date() == date)
+            .map(|r| r.duration_secs)
+            .sum(),
+        interruptions,
+        goal,
+        numbers,
+    }
+}
+
+impl DaySummary {
+    /// 通知用的一行摘要
+    pub fn headline(&self) -> String {
+        let numbers = self.numbers;
+        let mut line = format!(
+            "{} · {} focus",
+            numbers.plural(self.totals.sessions, "session"),
+            numbers.duration(self.totals.focus_secs)
+        );
+        if let Some(goal) = self.goal {
+            let _ = write!(
+                line,
+                " · goal {}/{}",
+                numbers.count(self.totals.sessions.into()),
+                numbers.count(goal.into())
+            );
+        }
+        line
+    }
+
+    pub fn recap(&self) -> String {
+        let numbers = self.numbers;
+        let mut out = String::new();
+        let _ = writeln!(out, "Summary for {}", self.date);
+        let _ = writeln!(
+            out,
+            "Focus          {}",
+            numbers.duration(self.totals.focus_secs)
+        );
+        let _ = writeln!(
+            out,
+            "Sessions       {}",
+            numbers.count(self.totals.sessions.into())
+        );
+        if let Some(ratio) = focus_break_ratio(self.totals.focus_secs, self.break_secs) {
+            let _ = writeln!(out, "Focus:Break    {ratio}");
+        }
+        if let Some(interruptions) = self.interruptions {
+            let _ = writeln!(
+                out,
+                "Interruptions  {}",
+                numbers.count(interruptions.into())
+            );
+        }
+        if let Some(goal) = self.goal {
+            let done = self.totals.sessions;
+            let verdict = match done.cmp(&goal) {
+                Ordering::Less => format!("{} to go", numbers.count((goal - done).into())),
+                Ordering::Equal => "reached".to_string(),
+                Ordering::Greater => {
+                    format!("reached, +{} extra", numbers.count((done - goal).into()))
+                }
+            };
+            let _ = writeln!(
+                out,
+                "Goal           {} / {} ({verdict})",
+                numbers.count(done.into()),
+                numbers.count(goal.into())
+            );
+        }
+        out
+    }
+}
+
+/// 专注与休息的比例，如 `5:1`、`1:1.5`；没有专注时为 `None`
+pub fn focus_break_ratio(focus_secs: u64, break_secs: u64) -> Option<String> {
+    if focus_secs < 60 {
+        return None;
+    }
+    if break_secs == 0 {
+        return Some("no breaks".to_string());
+    }
+    let ratio = focus_secs as f64 / break_secs as f64;
+    let trim = |value: f64| {
+        let text = format!("{value:.1}");
+        text.strip_suffix(".0").map(str::to_string).unwrap_or(text)
+    };
+    Some(if ratio >= 1.0 {
+        format!("{}:1", trim(ratio))
+    } else {
+        format!("1:{}", trim(1.0 / ratio))
+    })
+}
+
+/// `pomodoros summary`：打印某天的回顾
+pub fn run_summary(
+    path: &Path,
+    saved: Option<&SavedState>,
+    goal: Option<u32>,
+    date: NaiveDate,
+    numbers: NumberFormat,
+) -> Result<()> {
+    let records = history::load(path)?;
+    let interruptions = saved.filter(|s| s.day == date).map(|s| s.day_interruptions);
+    print!(
+        "{}",
+        day_summary(&records, date, interruptions, goal, numbers).recap()
+    );
+    Ok(())
+}
+
+pub fn run(
+    path: &Path,
+    saved: Option<&SavedState>,
+    goal: Option<u32>,
+    debt: Option<DebtRules>,
+    today: NaiveDate,
+    numbers: NumberFormat,
+    ascii: bool,
+) -> Result<()> {
+    let records = history::load(path)?;
+    let days = focus_by_day(&records);
+    let goals = history::load_goals(&history::goal_path(path))?;
+    let today_totals = days.get(&today).copied().unwrap_or_default();
+    let lifetime = days
+        .values()
+        .fold(DayTotals::default(), |acc, d| DayTotals {
+            sessions: acc.sessions + d.sessions,
+            focus_secs: acc.focus_secs + d.focus_secs,
+        });
+
+    let average = rolling_average(&days, today, AVERAGE_DAYS);
+    // 与日均专注时长相比，今天多了还是少了
+    let trend = average.map_or("", |avg| {
+        let focus = today_totals.focus_secs as f64;
+        if focus > avg.focus_secs {
+            if ascii { "  ^" } else { "  ↑" }
+        } else if focus < avg.focus_secs {
+            if ascii { "  v" } else { "  ↓" }
+        } else {
+            "  ="
+        }
+    });
+
+    let mut out = String::new();
+    let _ = writeln!(
+        out,
+        "Today      {}  ·  {} focus{trend}",
+        numbers.plural(today_totals.sessions, "session"),
+        numbers.duration(today_totals.focus_secs)
+    );
+    if let Some(avg) = average {
+        let _ = writeln!(
+            out,
+            "{:<11}{} sessions  ·  {} focus a day",
+            format!("{AVERAGE_DAYS}-day avg"),
+            numbers.decimal(avg.sessions),
+            numbers.duration(avg.focus_secs.round() as u64)
+        );
+    }
+    if let Some(goal) = goal {
+        let _ = writeln!(
+            out,
+            "Goal       {} / {}",
+            numbers.count(today_totals.sessions.into()),
+            numbers.count(goal.into())
+        );
+    }
+    if let Some(week) = goal_week(&days, &goals, goal, today, ascii) {
+        let _ = writeln!(out, "Last 7d    {week}");
+    }
+    if let Some(rules) = debt {
+        let carried = carried_debt(&days, rules, saved.and_then(|s| s.debt_reset), today);
+        let surplus = today_totals.sessions.saturating_sub(rules.goal);
+        let behind = carried.saturating_sub(surplus);
+        if behind == 0 {
+            let _ = writeln!(out, "Debt       On track");
+        } else {
+            let _ = writeln!(out, "Debt       Behind by {}", numbers.count(behind.into()));
+        }
+    }
+    if let Some(saved) = saved {
+        let _ = writeln!(out, "Streak     {}d", saved.current_streak(today));
+    }
+    let _ = writeln!(
+        out,
+        "Lifetime   {}  ·  {} focus",
+        numbers.plural(lifetime.sessions, "session"),
+        numbers.duration(lifetime.focus_secs)
+    );
+    print!("{out}");
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn day(offset: i64) -> NaiveDate {
+        NaiveDate::from_ymd_opt(2024, 6, 30).unwrap() + TimeDelta::days(offset)
+    }
+
+    fn totals(entries: &[(i64, u32, u64)]) -> BTreeMap<NaiveDate, DayTotals> {
+        entries
+            .iter()
+            .map(|&(offset, sessions, focus_secs)| {
+                (
+                    day(offset),
+                    DayTotals {
+                        sessions,
+                        focus_secs,
+                    },
+                )
+            })
+            .collect()
+    }
+
+    #[test]
+    fn records_are_bucketed_by_local_date_across_the_spring_transition() {
+        use crate::history::parse_line_in;
+        use crate::history::tests::CentralEurope;
+
+        let records: Vec<SessionRecord> = [
+            // 跨过 02:00→03:00 的那一夜，起止的偏移量不同，仍算在 3 月 31 日
+            "2024-03-31T01:40:00+01:00,2024-03-31T03:10:00+02:00,focus,1800,,0,,true",
+            // 按夏令时前的偏移量写下的时间，换算成夏令时后已是 4 月 1 日凌晨
+            "2024-03-31T23:05:00+01:00,2024-03-31T23:30:00+01:00,focus,1500,,0,,true",
+        ]
+        .iter()
+        .map(|line| parse_line_in(&CentralEurope, line).unwrap())
+        .collect();
+        let march_31 = NaiveDate::from_ymd_opt(2024, 3, 31).unwrap();
+        let april_1 = march_31 + TimeDelta::days(1);
+
+        let days = focus_by_day(&records);
+        assert_eq!(days.len(), 2);
+        assert_eq!(days[&march_31].sessions, 1);
+        assert_eq!(days[&march_31].focus_secs, 1800);
+        assert_eq!(days[&april_1].sessions, 1);
+        assert_eq!(days[&april_1].focus_secs, 1500);
+        // 4 月 1 日是周一，第二条记录属于下一周
+        assert_eq!(week_sessions(&records, march_31), 1);
+        assert_eq!(week_sessions(&records, april_1), 1);
+    }
+
+    #[test]
+    fn the_day_summary_groups_numbers_by_locale() {
+        let start = day(0).and_hms_opt(0, 0, 0).unwrap();
+        let records: Vec<SessionRecord> = (0..1204)
+            .map(|_| SessionRecord {
+                start,
+                end: start + TimeDelta::hours(1),
+                phase: Phase::Focus,
+                duration_secs: 3000,
+                batch: None,
+                interruptions: 0,
+                note: None,
+                completed: true,
+            })
+            .collect();
+        let headline = |tag: &str| {
+            day_summary(
+                &records,
+                day(0),
+                None,
+                Some(1500),
+                NumberFormat::for_locale(tag),
+            )
+            .headline()
+        };
+        assert_eq!(
+            headline("en"),
+            "1,204 sessions · 1,003h 20m focus · goal 1,204/1,500"
+        );
+        assert_eq!(
+            headline("de_DE.UTF-8"),
+            "1.204 sessions · 1.003h 20m focus · goal 1.204/1.500"
+        );
+        assert_eq!(
+            headline("fr-CA"),
+            "1\u{a0}204 sessions · 1\u{a0}003h 20m focus · goal 1\u{a0}204/1\u{a0}500"
+        );
+    }
+
+    #[test]
+    fn rolling_average_zero_fills_a_short_history() {
+        // 只有三天前的一天有记录：仍然按整个窗口平均，不会虚高
+        let days = totals(&[(-3, 4, 4 * 1500)]);
+        let average = rolling_average(&days, day(0), 28).unwrap();
+        assert_eq!(average.sessions, 4.0 / 28.0);
+        assert_eq!(average.focus_secs, 6000.0 / 28.0);
+    }
+
+    #[test]
+    fn rolling_average_covers_exactly_the_window_before_today() {
+        let days = totals(&[(-8, 70, 0), (-7, 7, 700), (-1, 14, 1400), (0, 100, 100_000)]);
+        let average = rolling_average(&days, day(0), 7).unwrap();
+        // 8 天前和今天都不在窗口里
+        assert_eq!(average.sessions, 3.0);
+        assert_eq!(average.focus_secs, 300.0);
+    }
+
+    #[test]
+    fn rolling_average_needs_a_day_before_today() {
+        assert!(rolling_average(&BTreeMap::new(), day(0), 28).is_none());
+        assert!(rolling_average(&totals(&[(0, 3, 4500)]), day(0), 28).is_none());
+        // 窗口之外的旧记录也算有历史，窗口内按零计
+        let old = rolling_average(&totals(&[(-90, 3, 4500)]), day(0), 28).unwrap();
+        assert_eq!(old.sessions, 0.0);
+    }
+}
@@ -0,0 +1,130 @@
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use anyhow::{Context, Result};
+use chrono::Utc;
+use serde::{Deserialize, Serialize};
+
+use crate::Phase;
+use crate::config::PhaseNames;
+use crate::state::SavedState;
+
+/// 运行中的实例每秒刷新，超过这个时间没有更新视为实例已退出
+const STALE_AFTER_MS: i64 = 10_000;
+
+/// 运行中的实例发布的实时状态，供 `pomodoros status` 读取
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct LiveStatus {
+    pub phase: Phase,
+    pub total_secs: u64,
+    pub remaining_secs: u64,
+    pub running: bool,
+    pub completed_focus: u32,
+    pub day_completed: u32,
+    /// 写入时间（Unix 毫秒）
+    pub updated_at: i64,
+}
+
+/// 实时状态文件与状态文件放在一起：`state.json` → `state.status.json`
+pub fn path_for(state: &Path) -> PathBuf {
+    state.with_extension("status.json")
+}
+
+pub fn publish(path: &Path, status: &LiveStatus) -> Result<()> {
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent)
+            .with_context(|| format!("failed to create {}", parent.display()))?;
+    }
+    let text = serde_json::to_string(status)?;
+    let tmp = path.with_extension("json.tmp");
+    fs::write(&tmp, text).with_context(|| format!("failed to write {}", tmp.display()))?;
+    fs::rename(&tmp, path).with_context(|| format!("failed to write {}", path.display()))?;
+    Ok(())
+}
+
+/// 退出时删除，避免状态栏显示过期的倒计时
+pub fn clear(path: &Path) {
+    let _ = fs::remove_file(path);
+}
+
+fn load(path: &Path) -> Option<LiveStatus> {
+    let text = fs::read_to_string(path).ok()?;
+    serde_json::from_str(&text).ok()
+}
+
+/// 按模板输出一行状态。没有运行中的实例时使用保存的状态（`{state}` 为 `stopped`）。
+/// 支持的占位符：`{phase}` `{phase_key}` `{time}` `{elapsed}` `{percent}` `{state}` `{completed}` `{today}`
+pub fn run(
+    path: &Path,
+    saved: Option<&SavedState>,
+    format: &str,
+    names: &PhaseNames,
+) -> Result<()> {
+    let now = Utc::now().timestamp_millis();
+    let live = load(path).filter(|live| now - live.updated_at < STALE_AFTER_MS);
+    let line = match (live, saved) {
+        (Some(live), _) => {
+            let remaining = if live.running {
+                let passed = u64::try_from((now - live.updated_at).max(0)).unwrap_or(0) / 1000;
+                live.remaining_secs.saturating_sub(passed)
+            } else {
+                live.remaining_secs
+            };
+            let state = if live.running { "running" } else { "paused" };
+            render(
+                format,
+                names,
+                &Fields {
+                    phase: live.phase,
+                    total_secs: live.total_secs,
+                    remaining_secs: remaining,
+                    state,
+                    completed: live.completed_focus,
+                    today: live.day_completed,
+                },
+            )
+        }
+        (None, Some(saved)) => render(
+            format,
+            names,
+            &Fields {
+                phase: saved.phase,
+                total_secs: saved.total_secs,
+                remaining_secs: saved.remaining_secs,
+                state: "stopped",
+                completed: saved.completed_focus,
+                today: saved.day_completed,
+            },
+        ),
+        (None, None) => String::new(),
+    };
+    println!("{line}");
+    Ok(())
+}
+
+struct Fields {
+    phase: Phase,
+    total_secs: u64,
+    remaining_secs: u64,
+    state: &'static str,
+    completed: u32,
+    today: u32,
+}
+
+fn render(format: &str, names: &PhaseNames, fields: &Fields) -> String {
+    let elapsed = fields.total_secs.saturating_sub(fields.remaining_secs);
+    let percent = elapsed
+        .saturating_mul(100)
+        .checked_div(fields.total_secs)
+        .unwrap_or(0);
+    let clock = |secs: u64| format!("{:02}:{:02}", secs / 60, secs % 60);
+    format
+        .replace("{phase}", names.get(fields.phase))
+        .replace("{phase_key}", fields.phase.key())
+        .replace("{time}", &clock(fields.remaining_secs))
+        .replace("{elapsed}", &clock(elapsed))
+        .replace("{percent}", &percent.to_string())
+        .replace("{state}", fields.state)
+        .replace("{completed}", &fields.completed.to_string())
+        .replace("{today}", &fields.today.to_string())
+}
@@ -0,0 +1,131 @@
+use std::fs;
+use std::path::Path;
+use std::str::FromStr;
+
+use anyhow::{Context, Result, anyhow};
+use ratatui::style::Color;
+use serde::Deserialize;
+
+use crate::Phase;
+use crate::config::Palette;
+
+/// 界面用到的全部颜色；默认值来自 `--palette`，`--theme-file` 可以逐项覆盖
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Theme {
+    pub focus: Color,
+    pub short_break: Color,
+    pub long_break: Color,
+    /// 进度条已完成部分；`None` 时用当前阶段的颜色
+    pub gauge_fg: Option<Color>,
+    pub gauge_bg: Color,
+    /// 面板边框；`None` 时用终端的默认前景色
+    pub border: Option<Color>,
+    /// 主要文字：计时数字、提示消息
+    pub text: Color,
+    /// 次要文字：状态行、完成数
+    pub muted: Color,
+    /// 最淡的文字：时钟、专注比例、进度条的空白部分
+    pub faint: Color,
+    /// 帮助面板里的按键
+    pub keys: Color,
+    /// 最后一分钟的边框、最后几秒的数字、超时和警告
+    pub warning: Color,
+}
+
+impl Theme {
+    pub fn new(palette: Palette) -> Self {
+        let (focus, short_break, long_break) = match palette {
+            Palette::Default => (Color::LightGreen, Color::Cyan, Color::Magenta),
+            // Okabe–Ito 配色中的橙、天蓝、蓝，取 256 色里最接近的
+            Palette::Colorblind => (Color::Indexed(214), Color::Indexed(117), Color::Indexed(33)),
+        };
+        Self {
+            focus,
+            short_break,
+            long_break,
+            gauge_fg: None,
+            gauge_bg: Color::Black,
+            border: None,
+            text: Color::White,
+            muted: Color::Gray,
+            faint: Color::DarkGray,
+            keys: Color::Yellow,
+            warning: Color::LightRed,
+        }
+    }
+
+    pub fn accent(&self, phase: Phase) -> Color {
+        match phase {
+            Phase::Focus => self.focus,
+            Phase::ShortBreak => self.short_break,
+            Phase::LongBreak => self.long_break,
+        }
+    }
+}
+
+/// 主题文件的内容，所有键都可省略
+#[derive(Debug, Default, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct ThemeFile {
+    focus: Option<String>,
+    short_break: Option<String>,
+    long_break: Option<String>,
+    gauge_fg: Option<String>,
+    gauge_bg: Option<String>,
+    border: Option<String>,
+    text: Option<String>,
+    muted: Option<String>,
+    faint: Option<String>,
+    keys: Option<String>,
+    warning: Option<String>,
+}
+
+/// 读取 TOML（`.json` 结尾时按 JSON）主题文件，在 `palette` 的基础上覆盖写了的颜色。
+/// 颜色写法：`"lightgreen"` 这类名称、`"#rrggbb"` 或 256 色序号 `"214"`；写错时报出是哪个键
+pub fn load(path: &Path, palette: Palette) -> Result<Theme> {
+    let text = fs::read_to_string(path)
+        .with_context(|| format!("failed to read theme file {}", path.display()))?;
+    let is_json = path
+        .extension()
+        .is_some_and(|ext| ext.eq_ignore_ascii_case("json"));
+    let file: ThemeFile = if is_json {
+        serde_json::from_str(&text).map_err(|err| anyhow!("{err}"))
+    } else {
+        toml::from_str(&text).map_err(|err| anyhow!("{err}"))
+    }
+    .with_context(|| format!("invalid theme file {}", path.display()))?;
+
+    let mut theme = Theme::new(palette);
+    let color = |key: &str, value: &Option<String>| -> Result<Option<Color>> {
+        value
+            .as_deref()
+            .map(|text| {
+                Color::from_str(text.trim()).map_err(|_| {
+                    anyhow!(
+                        "{}: `{key}` is not a color: \"{text}\" \
+                         (use a name like \"lightgreen\", \"#rrggbb\" or a 256-color index like \"214\")",
+                        path.display()
+                    )
+                })
+            })
+            .transpose()
+    };
+    let set = |target: &mut Color, key: &str, value: &Option<String>| -> Result<()> {
+        if let Some(parsed) = color(key, value)? {
+            *target = parsed;
+        }
+        Ok(())
+    };
+    set(&mut theme.focus, "focus", &file.focus)?;
+    set(&mut theme.short_break, "short_break", &file.short_break)?;
+    set(&mut theme.long_break, "long_break", &file.long_break)?;
+    set(&mut theme.gauge_bg, "gauge_bg", &file.gauge_bg)?;
+    set(&mut theme.text, "text", &file.text)?;
+    set(&mut theme.muted, "muted", &file.muted)?;
+    set(&mut theme.faint, "faint", &file.faint)?;
+    set(&mut theme.keys, "keys", &file.keys)?;
+    set(&mut theme.warning, "warning", &file.warning)?;
+    theme.gauge_fg = color("gauge_fg", &file.gauge_fg)?;
+    theme.border = color("border", &file.border)?;
+    Ok(theme)
+}
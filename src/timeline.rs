@@ -0,0 +1,205 @@
+use std::fmt::Write as _;
+use std::path::Path;
+
+use anyhow::Result;
+use chrono::{NaiveDate, NaiveDateTime, TimeDelta};
+
+use crate::Phase;
+use crate::config::PhaseNames;
+use crate::history::{self, SessionRecord};
+
+const BAR_WIDTH: i64 = 40;
+
+pub fn run(path: &Path, date: NaiveDate, gap_minutes: u64, names: &PhaseNames) -> Result<()> {
+    let records = history::load(path)?;
+    print!("{}", render(&records, date, gap_minutes, names));
+    Ok(())
+}
+
+/// 渲染某一天的甘特式时间线；跨午夜的阶段按当天边界截断显示
+pub fn render(
+    records: &[SessionRecord],
+    date: NaiveDate,
+    gap_minutes: u64,
+    names: &PhaseNames,
+) -> String {
+    let day_start = date.and_hms_opt(0, 0, 0).expect("midnight is a valid time");
+    let day_end = day_start + TimeDelta::days(1);
+
+    let mut sessions: Vec<&SessionRecord> = records
+        .iter()
+        .filter(|r| r.start < day_end && r.end > day_start)
+        .collect();
+    sessions.sort_by_key(|r| r.start);
+
+    let mut out = String::new();
+    let _ = writeln!(out, "Timeline for {date}");
+    if sessions.is_empty() {
+        let _ = writeln!(out, "No sessions recorded on this day.");
+        return out;
+    }
+
+    let clip = |t: NaiveDateTime| t.clamp(day_start, day_end);
+    let window_start = clip(sessions[0].start);
+    let window_end = sessions
+        .iter()
+        .map(|r| clip(r.end))
+        .max()
+        .unwrap_or(day_end);
+    let window = (window_end - window_start).num_seconds().max(1);
+    let column = |t: NaiveDateTime| (clip(t) - window_start).num_seconds() * BAR_WIDTH / window;
+
+    let gap_threshold = i64::try_from(gap_minutes)
+        .ok()
+        .and_then(TimeDelta::try_minutes)
+        .unwrap_or(TimeDelta::MAX);
+    let mut focus_secs = 0;
+    let mut break_secs = 0;
+    let mut focus_count = 0;
+    let mut prev_end: Option<NaiveDateTime> = None;
+
+    for record in &sessions {
+        if let Some(prev_end) = prev_end {
+            let gap = record.start - prev_end;
+            if gap >= TimeDelta::minutes(1) {
+                let note = if gap >= gap_threshold {
+                    "  ← break from work"
+                } else {
+                    ""
+                };
+                let _ = writeln!(
+                    out,
+                    "             ┄ gap {}{}",
+                    format_secs(gap.num_seconds().unsigned_abs()),
+                    note
+                );
+            }
+        }
+        prev_end = Some(prev_end.map_or(record.end, |p| p.max(record.end)));
+
+        let from = column(record.start);
+        let to = column(record.end).max(from + 1).min(BAR_WIDTH);
+        let fill = if record.phase == Phase::Focus {
+            "█"
+        } else {
+            "░"
+        };
+        let bar = format!(
+            "{}{}",
+            " ".repeat(from as usize),
+            fill.repeat((to - from).max(0) as usize)
+        );
+
+        let mut crossing = String::new();
+        if !record.completed {
+            crossing.push_str("  (partial)");
+        }
+        if record.start < day_start {
+            let _ = write!(crossing, "  (from {})", record.start.date());
+        }
+        if record.end > day_end {
+            let _ = write!(crossing, "  (until {})", record.end.date());
+        }
+
+        // 跨午夜的阶段只算落在当天的那一段；暂停过的阶段计时短于起止间隔，取两者较小值
+        let secs = if record.start < day_start || record.end > day_end {
+            let overlap = (clip(record.end) - clip(record.start)).num_seconds();
+            record.duration_secs.min(overlap.unsigned_abs())
+        } else {
+            record.duration_secs
+        };
+
+        let _ = writeln!(
+            out,
+            "{}–{}  {:<11} {:>7}  |{:<width$}|{}",
+            record.start.format("%H:%M"),
+            record.end.format("%H:%M"),
+            names.get(record.phase),
+            format_secs(secs),
+            bar,
+            crossing,
+            width = BAR_WIDTH as usize
+        );
+
+        if record.phase == Phase::Focus {
+            focus_secs += secs;
+            // 跨午夜的阶段只在结束的那天计一次
+            focus_count += u32::from(record.completed && record.end <= day_end);
+        } else {
+            break_secs += secs;
+        }
+    }
+
+    let _ = writeln!(
+        out,
+        "Focus {} in {} sessions  ·  Breaks {}",
+        format_secs(focus_secs),
+        focus_count,
+        format_secs(break_secs)
+    );
+    out
+}
+
+/// 25m / 1h 05m 形式的时长
+pub fn format_secs(secs: u64) -> String {
+    let minutes = (secs + 30) / 60;
+    if minutes >= 60 {
+        format!("{}h {:02}m", minutes / 60, minutes % 60)
+    } else {
+        format!("{minutes}m")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn at(text: &str) -> NaiveDateTime {
+        NaiveDateTime::parse_from_str(text, "%Y-%m-%d %H:%M").unwrap()
+    }
+
+    fn record(start: &str, end: &str, phase: Phase) -> SessionRecord {
+        let (start, end) = (at(start), at(end));
+        SessionRecord {
+            start,
+            end,
+            phase,
+            duration_secs: (end - start).num_seconds().unsigned_abs(),
+            batch: None,
+            interruptions: 0,
+            note: None,
+            completed: true,
+        }
+    }
+
+    #[test]
+    fn a_session_across_midnight_is_split_between_the_two_days() {
+        let records = [
+            record("2024-03-04 22:00", "2024-03-04 22:25", Phase::Focus),
+            record("2024-03-04 23:50", "2024-03-05 00:15", Phase::Focus),
+            record("2024-03-05 00:15", "2024-03-05 00:20", Phase::ShortBreak),
+        ];
+        let names = PhaseNames::default();
+
+        let first = render(&records, at("2024-03-04 00:00").date(), 30, &names);
+        assert!(
+            first.contains("23:50–00:15  Focus           10m"),
+            "{first}"
+        );
+        // 只算到午夜的 10 分钟，且这一节留给结束的那天去计数
+        assert!(
+            first.ends_with("Focus 35m in 1 sessions  ·  Breaks 0m\n"),
+            "{first}"
+        );
+
+        let second = render(&records, at("2024-03-05 00:00").date(), 30, &names);
+        assert!(
+            second.contains("23:50–00:15  Focus           15m"),
+            "{second}"
+        );
+        assert!(
+            second.ends_with("Focus 15m in 1 sessions  ·  Breaks 5m\n"),
+            "{second}"
+        );
+    }
+}
@@ -0,0 +1,56 @@
+use std::io::{self, IsTerminal, Write};
+use std::thread;
+use std::time::Duration;
+
+use anyhow::Result;
+
+use crate::{PomodoroApp, PomodoroConfig, add_default_observers, format_clock};
+
+const POLL: Duration = Duration::from_millis(100);
+
+/// 不启动界面，只跑一次专注倒计时；剩余时间以单行刷新的形式写到 stderr
+pub fn run(mut config: PomodoroConfig, focus: Duration, quiet: bool) -> Result<()> {
+    config.focus = focus;
+    // 配置里的 `start_phase` 不适用：总是从专注开始
+    config.start_phase = None;
+    config.ready_countdown = Duration::ZERO;
+    config.warmup = Duration::ZERO;
+    config.overrun = false;
+    config.alert_until_ack = false;
+    config.animations = false;
+    // 脚本里 stdout 通常被重定向，不往里面写响铃
+    config.mute |= !io::stdout().is_terminal();
+    let live = !quiet && io::stderr().is_terminal();
+
+    let mut app = PomodoroApp::new(config);
+    add_default_observers(&mut app);
+    app.toggle();
+    let mut stderr = io::stderr();
+    let mut shown = String::new();
+    // 只有专注真正到点才结束，而不是看当前阶段
+    let before = app.completed_focus;
+    while app.completed_focus == before {
+        app.update();
+        let text = app.formatted_remaining();
+        if live && text != shown {
+            let _ = write!(
+                stderr,
+                "\r{} {} {text} ",
+                app.config.icons.running, app.config.names.focus
+            );
+            let _ = stderr.flush();
+            shown = text;
+        }
+        thread::sleep(POLL);
+    }
+    if !quiet {
+        let _ = writeln!(
+            stderr,
+            "{}{} done ({})",
+            if live { "\r" } else { "" },
+            app.config.names.focus,
+            format_clock(focus)
+        );
+    }
+    Ok(())
+}